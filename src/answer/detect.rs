@@ -0,0 +1,129 @@
+//! Guess language of code from its content, for questions whose tags don't
+//! tell it.
+//!
+//! It's a rough heuristic which only knows a few popular languages, the
+//! question tags are always trusted first.
+
+/// Hints of each language, the syntect token goes first, then prefixes of
+/// lines and fragments which often appear in the code of the language.
+const HINTS: [(&str, &[&str], &[&str]); 4] = [
+    (
+        "py",
+        &[
+            "def ", "import ", "from ", "elif ", "class ", "print(", "return ",
+        ],
+        &["self.", "__init__", "None", "True:", "):\n", "in range("],
+    ),
+    (
+        "rs",
+        &[
+            "fn ", "pub fn ", "let ", "impl ", "use ", "struct ", "match ",
+        ],
+        &[
+            "let mut ", "::", "println!", "&str", "-> ", "Vec<", "unwrap()",
+        ],
+    ),
+    (
+        "js",
+        &["function ", "const ", "var ", "let ", "export ", "import "],
+        &["console.log", "=> ", "===", "document.", "require("],
+    ),
+    (
+        "sh",
+        &[
+            "$ ", "sudo ", "echo ", "export ", "cd ", "if [", "fi", "done", "for ", "apt-get ",
+        ],
+        &[" | ", "$(", "${", " && ", " --", "/dev/null"],
+    ),
+];
+
+/// The code needs at least the score to be guessed as a language.
+const MIN_SCORE: usize = 2;
+
+/// Guess language of the code by counting hints of each language.
+///
+/// `{` and `;` are counted too, C-like code is full of them, while python
+/// doesn't use them at all.
+///
+/// # Returns
+///
+/// The syntect token of the language, like `py`, or None if it can't be told.
+pub fn guess_language(code: &str) -> Option<&'static str> {
+    let lines: Vec<&str> = code
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    let punctuations: usize = code.matches(['{', ';']).count();
+    let c_like: bool = punctuations * 2 >= lines.len();
+
+    HINTS
+        .iter()
+        .map(|(token, prefixes, fragments)| {
+            let mut score: usize = lines
+                .iter()
+                .filter(|line| prefixes.iter().any(|prefix| line.starts_with(prefix)))
+                .count();
+            score += fragments
+                .iter()
+                .map(|fragment| code.matches(fragment).count())
+                .sum::<usize>();
+            if c_like && *token == "py" {
+                score /= 4;
+            }
+            (*token, score)
+        })
+        .filter(|(_, score)| *score >= MIN_SCORE)
+        // the former language wins when scores are equal.
+        .fold(
+            None,
+            |best: Option<(&str, usize)>, (token, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((token, score)),
+            },
+        )
+        .map(|(token, _)| token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_language() {
+        let python: &str = "def parse(data):
+    result = []
+    for line in data.splitlines():
+        if line:
+            result.append(line.strip())
+    return result
+";
+        assert_eq!(guess_language(python), Some("py"));
+
+        let rust: &str = "fn main() {
+    let mut names: Vec<String> = Vec::new();
+    names.push(String::from(\"hors\"));
+    println!(\"{:?}\", names);
+}
+";
+        assert_eq!(guess_language(rust), Some("rs"));
+
+        let shell: &str = "$ sudo apt-get install git
+$ git --version | grep 2.";
+        assert_eq!(guess_language(shell), Some("sh"));
+
+        let javascript: &str = "const items = [1, 2, 3];
+items.forEach((item) => console.log(item));";
+        assert_eq!(guess_language(javascript), Some("js"));
+    }
+
+    #[test]
+    fn test_guess_language_when_it_cant_be_told() {
+        assert_eq!(guess_language(""), None);
+        assert_eq!(guess_language("hello world"), None);
+        assert_eq!(guess_language("SELECT 1"), None);
+    }
+}
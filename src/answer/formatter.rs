@@ -0,0 +1,89 @@
+//! Post-process the output by an external formatter command, like `bat` or `glow`.
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+
+/// Pipe the output through the given formatter command.
+///
+/// The command is run by system shell, so it can contain arguments like
+/// `bat --language md`.
+///
+/// # Returns
+///
+/// Stdout of the formatter command, or the output itself if the command
+/// can't be run or exits with failure.  It blocks current thread until the
+/// command exits, so async code should call it on the blocking threads.
+pub fn format_with(command: &str, output: String) -> String {
+    match run_formatter(command, &output) {
+        Some(formatted) => formatted,
+        None => output,
+    }
+}
+
+fn run_formatter(command: &str, output: &str) -> Option<String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|err| warn!("Can't run formatter {:?}, error msg: {:?}", command, err))
+        .ok()?;
+    // write stdin in another thread, or the formatter may be blocked on a full stdout.
+    let mut stdin = child.stdin.take()?;
+    let input: String = output.to_string();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let formatted: Output = child.wait_with_output().ok()?;
+    if let Ok(Err(err)) = writer.join() {
+        debug!("Formatter doesn't read all output, error msg: {:?}", err);
+    }
+    if !formatted.status.success() {
+        warn!(
+            "Formatter {:?} exits with {}, use raw output.",
+            command, formatted.status
+        );
+        return None;
+    }
+    String::from_utf8(formatted.stdout).ok()
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell: Command = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell: Command = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_with() {
+        let output: String = String::from("println!(\"hors\")\n");
+        assert_eq!(
+            format_with("tr a-z A-Z", output),
+            String::from("PRINTLN!(\"HORS\")\n")
+        );
+    }
+
+    #[test]
+    fn test_format_with_when_formatter_fails() {
+        let output: String = String::from("println!(\"hors\")\n");
+        assert_eq!(
+            format_with("cat >/dev/null; exit 1", output.clone()),
+            output
+        );
+        assert_eq!(
+            format_with("hors-formatter-which-does-not-exist", output.clone()),
+            output
+        );
+    }
+}
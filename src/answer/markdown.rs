@@ -0,0 +1,187 @@
+//! Render answer body as markdown, so the output can be piped into markdown
+//! viewers.
+use select::node::Node;
+
+/// Render children of the answer body as markdown.
+///
+/// # Arguments
+///
+/// * `body` - the `post-text` node of answer.
+/// * `language` - language of fenced code blocks, e.g: `rust`.
+///
+/// # Returns
+///
+/// The markdown text, code blocks are fenced, lists are bullets and anchors are
+/// `[text](url)` links.
+pub fn to_markdown(body: Node, language: Option<&str>) -> String {
+    let mut markdown: String = String::new();
+    for child in body.children() {
+        render(child, language, &mut markdown);
+    }
+    markdown.trim().to_string()
+}
+
+fn render(node: Node, language: Option<&str>, out: &mut String) {
+    if let Some(text) = node.as_text() {
+        // it's whitespace between block elements.
+        if !(text.trim().is_empty() && text.contains('\n')) {
+            out.push_str(&collapse_whitespace(text));
+        }
+        return;
+    }
+    if let Some("pre") | Some("ul") | Some("ol") | Some("p") | Some("blockquote") = node.name() {
+        start_block(out);
+    }
+    match node.name() {
+        Some("pre") => out.push_str(&format!(
+            "```{}\n{}\n```\n\n",
+            language.unwrap_or_default(),
+            node.text().trim_end_matches('\n')
+        )),
+        Some("code") => out.push_str(&format!("`{}`", node.text())),
+        Some("a") => {
+            let text: String = inline(node, language);
+            match node.attr("href") {
+                Some(href) => out.push_str(&format!("[{}]({})", text, href)),
+                None => out.push_str(&text),
+            }
+        }
+        Some(list @ "ul") | Some(list @ "ol") => {
+            let items = node.children().filter(|child| child.name() == Some("li"));
+            for (index, item) in items.enumerate() {
+                let marker: String = if list == "ol" {
+                    format!("{}. ", index + 1)
+                } else {
+                    String::from("- ")
+                };
+                out.push_str(&indent_item(&marker, inline(item, language).trim()));
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        Some("p") => out.push_str(&format!("{}\n\n", inline(node, language).trim())),
+        Some(heading @ "h1") | Some(heading @ "h2") | Some(heading @ "h3")
+        | Some(heading @ "h4") | Some(heading @ "h5") | Some(heading @ "h6") => {
+            let level: usize = heading[1..].parse().unwrap_or(1);
+            out.push_str(&format!(
+                "{} {}\n\n",
+                "#".repeat(level),
+                inline(node, language).trim()
+            ));
+        }
+        Some("blockquote") => {
+            for line in inline(node, language).trim().lines() {
+                out.push_str(format!("> {}", line).trim_end());
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        Some("strong") | Some("b") => out.push_str(&format!("**{}**", inline(node, language))),
+        Some("em") | Some("i") => out.push_str(&format!("*{}*", inline(node, language))),
+        Some("br") => out.push('\n'),
+        Some("hr") => out.push_str("---\n\n"),
+        _ => {
+            for child in node.children() {
+                render(child, language, out);
+            }
+        }
+    }
+}
+
+/// Collapse whitespace like browsers do, e.g: line breaks in paragraph.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed: String = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if text.starts_with(char::is_whitespace) {
+        collapsed.insert(0, ' ');
+    }
+    if text.ends_with(char::is_whitespace) && !collapsed.ends_with(' ') {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+/// Make sure that block element starts at a new line, e.g: nested list.
+fn start_block(out: &mut String) {
+    let content_len: usize = out.trim_end_matches(' ').len();
+    out.truncate(content_len);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Render children of the node.
+fn inline(node: Node, language: Option<&str>) -> String {
+    let mut content: String = String::new();
+    for child in node.children() {
+        render(child, language, &mut content);
+    }
+    content
+}
+
+/// Prefix list item with the marker, and indent the rest lines under it.
+fn indent_item(marker: &str, content: &str) -> String {
+    let indent: String = " ".repeat(marker.len());
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => format!("{}{}", marker, line),
+            _ if line.is_empty() => String::new(),
+            _ => format!("{}{}", indent, line),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use select::document::Document;
+    use select::predicate::Class;
+
+    #[test]
+    fn test_to_markdown() {
+        let doc: Document = Document::from(
+            r#"
+        <div class="post-text">
+            <p>Use <code>serde_json</code>, see <a href="https://docs.serde.rs">the <em>docs</em></a>.</p>
+            <pre><code>let v: Value = serde_json::from_str(data)?;
+</code></pre>
+            <ul>
+                <li>parse into <strong>Value</strong></li>
+                <li>parse into struct
+                    <ol><li>derive Deserialize</li><li>call from_str</li></ol>
+                </li>
+            </ul>
+            <blockquote><p>It's fast.</p></blockquote>
+        </div>
+        "#,
+        );
+        let body: Node = doc.find(Class("post-text")).next().unwrap();
+        let expected: &str = "Use `serde_json`, see [the *docs*](https://docs.serde.rs).
+
+```rust
+let v: Value = serde_json::from_str(data)?;
+```
+
+- parse into **Value**
+- parse into struct
+  1. derive Deserialize
+  2. call from_str
+
+> It's fast.";
+        assert_eq!(to_markdown(body, Some("rust")), expected);
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        assert_eq!(collapse_whitespace("parse\n    json "), "parse json ");
+        assert_eq!(collapse_whitespace(" "), " ");
+    }
+
+    #[test]
+    fn test_indent_item() {
+        assert_eq!(indent_item("- ", "first\n\nsecond"), "- first\n\n  second");
+        assert_eq!(indent_item("10. ", "item"), "10. item");
+    }
+}
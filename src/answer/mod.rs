@@ -1,3 +1,13 @@
+mod detect;
+mod formatter;
+mod markdown;
+mod palette;
 mod precious;
 mod records;
-pub use precious::{get_answers, get_answers_with_client, SPLITTER};
+mod terminal;
+pub(crate) use precious::{api_answers_with_client, postprocess_async, raw_answers_with_client};
+pub use precious::{
+    get_answer_summaries, get_answer_summaries_with_client, get_answers, get_answers_from_file,
+    get_answers_streamed, get_answers_with_client, parse_answers_from_html, prefetch,
+    prefetch_with_client, AnswerSummary, SPLITTER,
+};
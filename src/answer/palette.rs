@@ -0,0 +1,104 @@
+//! Downsample highlighted code to the xterm 256 colors palette, for terminals
+//! which don't support truecolor.
+use syntect::highlighting::{Color, Style};
+
+/// Levels of each channel in the 6x6x6 color cube of xterm palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Make terminal escaped text with xterm 256 colors, it's the same as
+/// `syntect::util::as_24_bit_terminal_escaped` except the escapes.
+pub fn as_256_color_terminal_escaped(ranges: &[(Style, &str)], bg: bool) -> String {
+    let mut escaped: String = String::new();
+    for (style, text) in ranges {
+        if bg {
+            escaped.push_str(&format!("\x1b[48;5;{}m", ansi256(style.background)));
+        }
+        escaped.push_str(&format!("\x1b[38;5;{}m{}", ansi256(style.foreground), text));
+    }
+    escaped
+}
+
+/// Find the nearest color in xterm 256 colors palette.
+///
+/// The 16 system colors are skipped, because they are often customized by
+/// terminal themes.  So the color is in either the 6x6x6 color cube (16-231),
+/// or the grayscale ramp (232-255).
+fn ansi256(color: Color) -> u8 {
+    let rgb: [u8; 3] = [color.r, color.g, color.b];
+    let cube: [usize; 3] = [
+        nearest_level(color.r),
+        nearest_level(color.g),
+        nearest_level(color.b),
+    ];
+    let cube_color: [u8; 3] = [
+        CUBE_LEVELS[cube[0]],
+        CUBE_LEVELS[cube[1]],
+        CUBE_LEVELS[cube[2]],
+    ];
+    let cube_index: u8 = (16 + 36 * cube[0] + 6 * cube[1] + cube[2]) as u8;
+
+    // grays of the ramp are 8, 18, ..., 238.
+    let average: u32 = rgb.iter().map(|c| u32::from(*c)).sum::<u32>() / 3;
+    let gray_step: u32 = (average.saturating_sub(3) / 10).min(23);
+    let gray: u8 = (8 + 10 * gray_step) as u8;
+    let gray_index: u8 = 232 + gray_step as u8;
+
+    if distance(rgb, [gray; 3]) < distance(rgb, cube_color) {
+        gray_index
+    } else {
+        cube_index
+    }
+}
+
+fn nearest_level(channel: u8) -> usize {
+    (0..CUBE_LEVELS.len())
+        .min_by_key(|index| (i32::from(CUBE_LEVELS[*index]) - i32::from(channel)).abs())
+        .unwrap_or_default()
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (i32::from(*x) - i32::from(*y)).pow(2) as u32)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 0xFF }
+    }
+
+    #[test]
+    fn test_ansi256() {
+        assert_eq!(ansi256(rgb(0, 0, 0)), 16);
+        assert_eq!(ansi256(rgb(255, 255, 255)), 231);
+        assert_eq!(ansi256(rgb(255, 0, 0)), 196);
+        assert_eq!(ansi256(rgb(0, 135, 255)), 33);
+        // close to a cube color.
+        assert_eq!(ansi256(rgb(250, 5, 100)), 197);
+        // grays between cube levels go to the grayscale ramp.
+        assert_eq!(ansi256(rgb(118, 118, 118)), 243);
+        assert_eq!(ansi256(rgb(45, 45, 45)), 236);
+    }
+
+    #[test]
+    fn test_as_256_color_terminal_escaped() {
+        let style = Style {
+            foreground: rgb(255, 0, 0),
+            background: rgb(0, 0, 0),
+            ..Style::default()
+        };
+        let ranges: Vec<(Style, &str)> = vec![(style, "fn"), (style, " main")];
+        assert_eq!(
+            as_256_color_terminal_escaped(&ranges, false),
+            "\x1b[38;5;196mfn\x1b[38;5;196m main"
+        );
+        assert_eq!(
+            as_256_color_terminal_escaped(&ranges[..1], true),
+            "\x1b[48;5;16m\x1b[38;5;196mfn"
+        );
+    }
+}
@@ -1,20 +1,85 @@
 //! This module contains api to get results from stack overflow page.
 //! Yeah, our precious lays in stackoverflow.com.
 
+use super::detect::guess_language;
+use super::formatter::format_with;
+use super::markdown::to_markdown;
+use super::palette::as_256_color_terminal_escaped;
 use super::records::AnswerRecordsCache;
-use crate::config::{Config, OutputOption};
-use crate::error::Result;
-use crate::utils::random_agent;
-use reqwest::{Client, ClientBuilder, Response, Url};
+use super::terminal::{fit_to_terminal, truncate_answer};
+use crate::config::{
+    ColorDepth, Config, OutputOption, Rank, SearchEngine, DEFAULT_THEME, FAST_PATH_TIMEOUT,
+};
+use crate::engine::{answers_from, search_links_with_client, ApiAnswer, API_URL, MAX_IDS};
+use crate::error::{Error, Result};
+use crate::retry::send_with_retry;
+use crate::utils::{client_builder, is_question_link, question_id, random_agent};
+use futures::channel::mpsc;
+use futures::stream::{FuturesOrdered, StreamExt};
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url};
 use select::document::Document;
 use select::node::Node;
-use select::predicate::{Class, Name};
+use select::predicate::{Attr, Class, Name, Predicate};
+use serde::Serialize;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+use std::fs::{self, create_dir_all};
+use std::path::Path;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
+use syntect::highlighting::{Style, Theme, ThemeSet};
 use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+use tokio::task;
 
 pub const SPLITTER: &str = "\n^_^ ==================================================== ^_^\n\n";
+/// Splitter between answers of the same question.
+const ANSWER_SPLITTER: &str = "\n---------------------- next answer ----------------------\n\n";
+/// The `id_` flag makes Wayback Machine returns the original page, without it's toolbar.
+const WAYBACK_PREFIX: &str = "http://web.archive.org/web/2id_/";
+/// Cookie which tells stackoverflow that user has closed the consent banner.
+const CONSENT_COOKIE: &str = "OptanonAlertBoxClosed=2020-01-01T00:00:00.000Z";
+/// How many characters of answer are kept in the preview of summary.
+const PREVIEW_CHARS: usize = 80;
+/// How many comments are output under the answer.
+const MAX_COMMENTS: usize = 3;
+/// Text which indicates that the page is a cookie consent banner.
+const CONSENT_MARKERS: [&str; 2] = ["onetrust-consent-sdk", "js-consent-banner"];
+/// Stackoverflow tags which are not syntect tokens, and the tokens of their syntaxes.
+const SYNTAX_ALIASES: [(&str, &str); 12] = [
+    ("node.js", "js"),
+    ("ecmascript-6", "js"),
+    ("golang", "go"),
+    ("c#", "cs"),
+    ("c++", "cpp"),
+    ("c++11", "cpp"),
+    ("c++17", "cpp"),
+    ("python-3.x", "py"),
+    ("python-2.7", "py"),
+    ("shell", "sh"),
+    ("objective-c", "m"),
+    ("vb.net", "vb"),
+];
+
+/// Syntaxes and themes to colorize code, they are loaded only once because
+/// loading them is expensive.
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// Get syntaxes to colorize code, the custom ones of user goes first.
+fn syntax_set(config: &Config) -> &SyntaxSet {
+    config.syntax_set().unwrap_or(&SYNTAX_SET)
+}
+
+/// Get theme to colorize code, the theme file of user goes first, then the
+/// theme name, and `DEFAULT_THEME` is used if the name is unknown.
+fn theme(config: &Config) -> &Theme {
+    config
+        .custom_theme()
+        .or_else(|| config.theme().and_then(|name| THEME_SET.themes.get(name)))
+        .unwrap_or(&THEME_SET.themes[DEFAULT_THEME])
+}
 
 /// Get answers from given links.
 ///
@@ -42,9 +107,10 @@ pub const SPLITTER: &str = "\n^_^ ==============================================
 /// # Returns
 ///
 /// If search answers successfully, it will return the result string which can be
-/// print to terminal directly.  Else return an Error.
+/// print to terminal directly.  Else return an Error, `Error::NoAnswer` if none
+/// of the links gives an answer.
 pub async fn get_answers(links: &[String], conf: Config) -> Result<String> {
-    let client: Client = ClientBuilder::new().cookie_store(true).build().unwrap();
+    let client: Client = client_builder(&conf)?.build()?;
     get_answers_with_client(links, conf, &client).await
 }
 
@@ -77,76 +143,789 @@ pub async fn get_answers(links: &[String], conf: Config) -> Result<String> {
 /// # Returns
 ///
 /// If search answers successfully, it will return the result string which can be
-/// print to terminal directly.  Else return an Error.
+/// print to terminal directly.  Else return an Error, `Error::NoAnswer` if none
+/// of the links gives an answer.
 pub async fn get_answers_with_client(
     links: &[String],
     conf: Config,
     client: &Client,
 ) -> Result<String> {
-    debug!("Try to load cache from local cache file.");
-    // load hors internal cache.
-    let load_result: Result<AnswerRecordsCache> = AnswerRecordsCache::load();
-    let mut records_cache: AnswerRecordsCache = match load_result {
-        Ok(cache) => cache,
+    let answers: String = raw_answers_with_client(links, &conf, client).await?;
+    Ok(postprocess_async(&conf, answers).await)
+}
+
+/// Get answers of the links through the given client, before they're
+/// post-processed, see `postprocess_async`.
+pub(crate) async fn raw_answers_with_client(
+    links: &[String],
+    conf: &Config,
+    client: &Client,
+) -> Result<String> {
+    let mut records_cache: AnswerRecordsCache = load_records_cache();
+    let results: Result<String> = match conf.option() {
+        OutputOption::Links => Ok(answers_links_only(
+            links,
+            conf.numbers() as usize,
+            conf.splitter(),
+        )),
+        _ => get_detailed_answer(links, conf, &mut records_cache, client).await,
+    };
+    save_records_cache(&records_cache, conf);
+    results
+}
+
+/// Get answers of the links through stackexchange api, which gives answers of
+/// all questions on a site in one request, rather than fetching the question
+/// pages one by one.  Like `raw_answers_with_client`, they're not post-processed.
+///
+/// It falls back to fetching the question pages when the api can't be used,
+/// e.g: the request quota is used up.
+pub(crate) async fn api_answers_with_client(
+    links: &[String],
+    conf: &Config,
+    client: &Client,
+) -> Result<String> {
+    if matches!(conf.option(), OutputOption::Links) {
+        return raw_answers_with_client(links, conf, client).await;
+    }
+    match api_answers_from(links, conf, client, API_URL).await {
+        Err(Error::NoAnswer(links)) => Err(Error::NoAnswer(links)),
         Err(err) => {
-            warn!("Can't load cache from local cache file, errmsg {:?}", err);
-            AnswerRecordsCache::load_empty()
+            warn!(
+                "Can't get answers from api, fetch question pages instead: {:?}",
+                err
+            );
+            raw_answers_with_client(links, conf, client).await
+        }
+        answers => answers,
+    }
+}
+
+async fn api_answers_from(
+    links: &[String],
+    conf: &Config,
+    client: &Client,
+    api_url: &str,
+) -> Result<String> {
+    // (link, site, question id) of each question link.
+    let questions: Vec<(&String, String, u64)> = links
+        .iter()
+        .filter(|link| is_question_link(link))
+        .filter_map(|link| {
+            let site: String = Url::parse(link).ok()?.host_str()?.to_string();
+            let id: u64 = question_id(link)?.parse().ok()?;
+            Some((link, site, id))
+        })
+        .collect();
+    let mut sites: Vec<&str> = questions.iter().map(|(_, site, _)| site.as_str()).collect();
+    sites.sort_unstable();
+    sites.dedup();
+
+    let mut answers: HashMap<(&str, u64), Vec<ApiAnswer>> = HashMap::new();
+    for site in sites {
+        let ids: Vec<u64> = questions
+            .iter()
+            .filter(|(_, question_site, _)| question_site == site)
+            .map(|(_, _, id)| *id)
+            .collect();
+        for chunk in ids.chunks(MAX_IDS) {
+            for answer in answers_from(chunk, site, conf, client, api_url).await? {
+                answers
+                    .entry((site, answer.question_id))
+                    .or_default()
+                    .push(answer);
+            }
+        }
+    }
+
+    let mut pages: Vec<(String, Answer)> = Vec::new();
+    for (link, site, id) in questions.iter() {
+        if pages.len() >= conf.numbers() as usize {
+            break;
+        }
+        let page: String = match answers.get(&(site.as_str(), *id)) {
+            Some(answers) => api_page(answers),
+            None => continue,
+        };
+        let answer: Answer = parse_question(link, &page, conf);
+        if answer.answer_text.is_some() {
+            info!("Parsed answer from {}", link);
+            pages.push((page, answer));
+        }
+    }
+    format_pages(pages, links, conf)
+}
+
+/// Make the answers given by api into a page which can be parsed like the
+/// question page.
+fn api_page(answers: &[ApiAnswer]) -> String {
+    answers
+        .iter()
+        .filter_map(|answer| {
+            let body: &String = answer.body.as_ref()?;
+            Some(format!(
+                r#"<div class="answer{}"><div class="js-vote-count" data-value="{}">{}</div><div class="post-text">{}</div></div>"#,
+                if answer.is_accepted { " accepted-answer" } else { "" },
+                answer.score,
+                answer.score,
+                body
+            ))
+        })
+        .collect()
+}
+
+/// Get answers from given links, and give each answer to `on_answer` as soon
+/// as it's parsed, so the caller can output the first answer while the rest
+/// are downloading.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{Config, OutputOption, SPLITTER};
+/// use reqwest::{Client, ClientBuilder};
+///
+/// # async fn run() {
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 3, false);
+/// let client: Client = ClientBuilder::new().cookie_store(true).build().unwrap();
+/// let links: Vec<String> = vec![
+///     String::from("https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json")
+/// ];
+/// hors::get_answers_streamed(&links, conf, &client, |answer: String| {
+///     print!("{}{}", answer, SPLITTER);
+/// })
+/// .await
+/// .unwrap();
+/// # }
+/// ```
+///
+/// # Returns
+///
+/// Answers are given in the order of links, they are the same as the parts of
+/// `get_answers_with_client` output which are joined by `conf.splitter()`.  Under
+/// `OutputOption::Json` each answer is a json object, and under
+/// `OutputOption::Links` all links are given at once.
+///
+/// Like `get_answers_with_client`, questions without answers are skipped and
+/// don't count toward `conf.numbers()`.  Error is returned if a question page
+/// can't be fetched before any answer is given, a later failure is logged and
+/// the answers before it are kept.
+pub async fn get_answers_streamed<F: FnMut(String)>(
+    links: &[String],
+    conf: Config,
+    client: &Client,
+    mut on_answer: F,
+) -> Result<()> {
+    if let OutputOption::Links = conf.option() {
+        let output: String = answers_links_only(links, conf.numbers() as usize, conf.splitter());
+        on_answer(postprocess_async(&conf, output).await);
+        return Ok(());
+    }
+    let mut records_cache: AnswerRecordsCache = load_records_cache();
+    let result: Result<()> =
+        stream_answers(links, &conf, &mut records_cache, client, on_answer).await;
+    save_records_cache(&records_cache, &conf);
+    result
+}
+
+async fn stream_answers<F: FnMut(String)>(
+    links: &[String],
+    conf: &Config,
+    records_cache: &mut AnswerRecordsCache,
+    client: &Client,
+    mut on_answer: F,
+) -> Result<()> {
+    // answers are post-processed while the following pages are fetching.
+    let (sender, mut receiver) = mpsc::unbounded::<String>();
+    let visiting = async move {
+        let mut is_top: bool = true;
+        let result: Result<()> = visit_answers(links, conf, records_cache, client, |page, answer| {
+            let output: String = match conf.option() {
+                OutputOption::Json => serde_json::to_string_pretty(&answer).expect(
+                    "Answer should be serialized :(  If you see this message, please fire an issue.",
+                ),
+                _ => format_answer(&page, &answer, is_top, conf),
+            };
+            is_top = false;
+            // the receiver lives until all answers are given.
+            let _ = sender.unbounded_send(output);
+        })
+        .await;
+        drop(sender);
+        result
+    };
+    let giving = async {
+        while let Some(output) = receiver.next().await {
+            on_answer(postprocess_async(conf, output).await);
         }
     };
-    debug!("Load cache complete.");
+    let (result, ()) = futures::join!(visiting, giving);
+    result
+}
 
-    let results: Result<String> = match conf.option() {
-        OutputOption::Links => Ok(answers_links_only(links, conf.numbers() as usize)),
-        _ => get_detailed_answer(links, conf, &mut records_cache, &client).await,
+/// A candidate answer of question, which is useful for callers to let user
+/// choose the answer to expand.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AnswerSummary {
+    /// Link to the question page.
+    pub link: String,
+    /// Title of the question.
+    pub title: String,
+    /// Votes of the answer.
+    pub score: i16,
+    /// Whether the answer is accepted by the question owner.
+    pub accepted: bool,
+    /// The first line of answer in plain text, it's cut off if it's too long.
+    pub preview: String,
+    /// The answer formatted according to user config, which is the same as
+    /// the answer in `get_answers` output.
+    pub body: String,
+}
+
+/// Get all candidate answers of the given links, the best answer of each
+/// question goes first.
+///
+/// Unlike `get_answers`, answers are not limited by
+/// `Config::answers_per_question` and they are not joined into one string.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{AnswerSummary, Config, OutputOption};
+///
+/// # async fn run() {
+/// let conf: Config = Config::new(OutputOption::All, 1, false);
+/// let links: Vec<String> = vec![
+///     String::from("https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json")
+/// ];
+/// let summaries: Vec<AnswerSummary> = hors::get_answer_summaries(&links, conf).await.unwrap();
+/// for (index, summary) in summaries.iter().enumerate() {
+///     println!("{}. [{}] {}", index + 1, summary.score, summary.preview);
+/// }
+/// # }
+/// ```
+///
+/// # Returns
+///
+/// The summaries of the first `conf.numbers()` questions which have answers,
+/// questions without answers don't count.  `Error::NoAnswer` is returned if
+/// none of the questions has answers, and an Error if a question page can't be
+/// fetched before any answer is found.  Summaries can't be made under
+/// `OutputOption::Links`, because no answer is parsed.
+pub async fn get_answer_summaries(links: &[String], conf: Config) -> Result<Vec<AnswerSummary>> {
+    let client: Client = client_builder(&conf)?.build()?;
+    get_answer_summaries_with_client(links, conf, &client).await
+}
+
+/// Get all candidate answers of the given links through the given client, see
+/// `get_answer_summaries` for details.
+pub async fn get_answer_summaries_with_client(
+    links: &[String],
+    conf: Config,
+    client: &Client,
+) -> Result<Vec<AnswerSummary>> {
+    if let OutputOption::Links = conf.option() {
+        return Err(Error::from_parse(
+            "Answer summaries can't be made under OutputOption::Links",
+        ));
+    }
+    let mut records_cache: AnswerRecordsCache = load_records_cache();
+    let result: Result<Vec<AnswerSummary>> =
+        answer_summaries(links, &conf, &mut records_cache, client).await;
+    save_records_cache(&records_cache, &conf);
+    result
+}
+
+async fn answer_summaries(
+    links: &[String],
+    conf: &Config,
+    records_cache: &mut AnswerRecordsCache,
+    client: &Client,
+) -> Result<Vec<AnswerSummary>> {
+    let mut summaries: Vec<AnswerSummary> = vec![];
+    visit_answers(links, conf, records_cache, client, |page, answer| {
+        summaries.extend(summarize(&page, &answer, conf))
+    })
+    .await?;
+    if conf.sites().len() > 1 {
+        summaries.sort_by_key(|summary| Reverse(summary.score));
+    }
+    Ok(summaries)
+}
+
+/// Make summary of each candidate answer of the question page.
+fn summarize(page: &str, answer: &Answer, config: &Config) -> Vec<AnswerSummary> {
+    let doc: Document = if config.bounded_parse() {
+        Document::from(bound_page(page).as_str())
+    } else {
+        Document::from(page)
     };
+    rank_answers(&doc, config)
+        .into_iter()
+        .filter_map(|candidate| {
+            let body: String = answer_content(candidate.node, &answer.question_tags, config)?;
+            Some(AnswerSummary {
+                link: answer.link.clone(),
+                title: answer.title.clone(),
+                score: candidate.votes,
+                accepted: candidate.accepted,
+                preview: answer_preview(candidate.node),
+                body,
+            })
+        })
+        .collect()
+}
+
+/// Get the first line of answer in plain text, which is cut off at
+/// `PREVIEW_CHARS` characters.
+fn answer_preview(answer: Node) -> String {
+    let text: String = find_answer_body(answer)
+        .map(|body| body.text())
+        .unwrap_or_default();
+    let line: &str = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() <= PREVIEW_CHARS {
+        return escape_control_chars(line);
+    }
+    let cut: String = line.chars().take(PREVIEW_CHARS - 1).collect();
+    escape_control_chars(&format!("{}…", cut.trim_end()))
+}
+
+/// Load hors internal cache, it's empty if the cache file can't be loaded.
+fn load_records_cache() -> AnswerRecordsCache {
+    debug!("Try to load cache from local cache file.");
+    match AnswerRecordsCache::load() {
+        Ok(cache) => {
+            debug!("Load cache complete.");
+            cache
+        }
+        Err(err) => {
+            warn!("Can't load cache from local cache file, errmsg {:?}", err);
+            AnswerRecordsCache::load_empty()
+        }
+    }
+}
 
-    // when hors gets what we wanted answer, save it for next time using.
-    if let Err(err) = records_cache.save() {
+/// Save hors internal cache, so the pages can be used next time.
+fn save_records_cache(records_cache: &AnswerRecordsCache, conf: &Config) {
+    if conf.fast_path() {
+        debug!("Skip saving cache under fast path.");
+    } else if let Err(err) = records_cache.save() {
         warn!(
             "Can't save cache into local directory, error msg: {:?}",
             err
         );
     }
-    results
+}
+
+/// Get answers from a question page saved on disk, without touching network.
+///
+/// The page goes through the same pipeline as the pages from network, so it's
+/// useful to reproduce parsing bugs against a saved page, e.g: saved by
+/// `Config::set_save_html_dir`.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{Config, OutputOption};
+/// use std::path::Path;
+///
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+/// let answers = hors::get_answers_from_file(
+///     Path::new("7771011.html"),
+///     "https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json",
+///     conf,
+/// );
+/// ```
+///
+/// # Arguments
+///
+/// * `path` - path to the saved question page.
+/// * `link` - link to the question page, which is used in output.
+/// * `conf` - user config.
+///
+/// # Returns
+///
+/// The answers which is the same as `get_answers` returns for the page, or
+/// an Error if the file can't be read, `Error::NoAnswer` if the page doesn't
+/// contain any answer.
+pub fn get_answers_from_file(path: &Path, link: &str, conf: Config) -> Result<String> {
+    let page: String = fs::read_to_string(path)?;
+    answer_page(link, page, &conf).map(postprocessor(&conf))
+}
+
+/// Parse and format answers of the question page, without touching network.
+///
+/// The page goes through the same parsing and formatting as each page fetched
+/// by `get_answers`, so it's useful to benchmark or test parsing against saved
+/// pages.  The link of question is taken from the canonical link of page, the
+/// header line of answer is omitted if the page doesn't have one.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{Config, OutputOption};
+///
+/// let page: &str = r#"<div class="answer"><div class="post-text"><pre><code>cargo new hors</code></pre></div></div>"#;
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+/// let answers: String = hors::parse_answers_from_html(page, &conf).unwrap();
+/// // the page has no canonical link, so there is no header line.
+/// assert_eq!(answers, "cargo new hors");
+/// ```
+///
+/// # Returns
+///
+/// The answers which is the same as `get_answers` returns for the page, or
+/// `Error::NoAnswer` if the page doesn't contain any answer.
+pub fn parse_answers_from_html(html: &str, conf: &Config) -> Result<String> {
+    let link: String = canonical_link(html).unwrap_or_default();
+    answer_page(&link, html.to_string(), conf).map(postprocessor(conf))
+}
+
+/// Parse and format answers of single question page, see `format_pages`.
+fn answer_page(link: &str, page: String, conf: &Config) -> Result<String> {
+    if let OutputOption::Links = conf.option() {
+        return Ok(answers_links_only(&[link.to_string()], 1, conf.splitter()));
+    }
+    let answer: Answer = parse_question(link, &page, conf);
+    format_pages(vec![(page, answer)], &[link.to_string()], conf)
+}
+
+/// Get the canonical link of question page, e.g: `<link rel="canonical">`.
+fn canonical_link(page: &str) -> Option<String> {
+    Document::from(page)
+        .find(Name("link").and(Attr("rel", "canonical")))
+        .find_map(|link| link.attr("href").map(String::from))
+}
+
+/// Post-process the output on the blocking threads, because the formatter
+/// command may run for a while, and it shouldn't block the async executor.
+pub(crate) async fn postprocess_async(conf: &Config, output: String) -> String {
+    let postprocess = postprocessor(conf);
+    if conf.formatter_cmd().is_none() {
+        return postprocess(output);
+    }
+    task::spawn_blocking(move || postprocess(output))
+        .await
+        .expect("Formatter shouldn't panic :(  If you see this message, please fire an issue.")
+}
+
+/// Make the function which post-processes the output, according to user config.
+///
+/// Json output is never post-processed, so it's always valid json.
+fn postprocessor(conf: &Config) -> impl Fn(String) -> String + Send + 'static {
+    let json: bool = matches!(conf.option(), OutputOption::Json);
+    let fit: bool = conf.fit_to_terminal() && !json;
+    let formatter: Option<String> = conf.formatter_cmd().filter(|_| !json).map(String::from);
+    move |mut output: String| {
+        if let Some(formatter) = &formatter {
+            output = format_with(formatter, output);
+        }
+        if fit {
+            output = fit_to_terminal(output);
+        }
+        output
+    }
+}
+
+/// Warm the cache for the given queries, so their answers are available
+/// when network is not, e.g. on a flight.
+///
+/// Each query is searched under the given search engine, and question pages
+/// of its first `conf.numbers()` links are saved into local cache, nothing
+/// is printed.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{Config, OutputOption, SearchEngine};
+///
+/// # async fn run() {
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+/// let queries: Vec<String> = vec![String::from("how to parse json in rust")];
+/// hors::prefetch(&queries, SearchEngine::Bing, &conf).await.unwrap();
+/// # }
+/// ```
+///
+/// # Returns
+///
+/// Error will be returned if any query can't be searched or fetched.
+pub async fn prefetch(
+    queries: &[String],
+    search_engine: SearchEngine,
+    conf: &Config,
+) -> Result<()> {
+    let client: Client = client_builder(conf)?.build()?;
+    prefetch_with_client(queries, search_engine, conf, &client).await
+}
+
+/// Warm the cache for the given queries through the given client, see
+/// `prefetch` for details.
+pub async fn prefetch_with_client(
+    queries: &[String],
+    search_engine: SearchEngine,
+    conf: &Config,
+    client: &Client,
+) -> Result<()> {
+    let mut records_cache: AnswerRecordsCache = match AnswerRecordsCache::load() {
+        Ok(cache) => cache,
+        Err(err) => {
+            warn!("Can't load cache from local cache file, errmsg {:?}", err);
+            AnswerRecordsCache::load_empty()
+        }
+    };
+    for query in queries {
+        let links: Vec<String> =
+            search_links_with_client(query, search_engine, conf, client).await?;
+        cache_pages(&links, conf, &mut records_cache, client).await?;
+    }
+    records_cache.save()
+}
+
+/// Put question pages of the first `conf.numbers()` links into cache.
+async fn cache_pages(
+    links: &[String],
+    conf: &Config,
+    records_cache: &mut AnswerRecordsCache,
+    client: &Client,
+) -> Result<()> {
+    let question_links = links.iter().filter(|link| is_question_link(link));
+    for link in question_links.take(conf.numbers() as usize) {
+        get_page(link, client, records_cache, conf).await?;
+    }
+    Ok(())
 }
 
 async fn get_detailed_answer(
     links: &[String],
-    conf: Config,
+    conf: &Config,
     records_cache: &mut AnswerRecordsCache,
     client: &Client,
 ) -> Result<String> {
-    let mut results: Vec<String> = Vec::new();
-    let mut links_iter = links.iter();
-
-    for _ in 0..conf.numbers() {
-        let next_link = links_iter.next();
-        match next_link {
-            Some(link) => {
-                // the given links may contains the url doesn't contains `question`
-                // tag, so it's not a question, just deal with nothing to it.
-                if !link.contains("question") {
-                    continue;
-                }
+    let mut pages: Vec<(String, Answer)> = Vec::new();
+    visit_answers(links, conf, records_cache, client, |page, answer| {
+        pages.push((page, answer))
+    })
+    .await?;
+    format_pages(pages, links, conf)
+}
+
+/// Format the parsed question pages as output, it's the same for pages from
+/// network, disk, or given by caller.
+///
+/// # Returns
+///
+/// `Error::NoAnswer` if none of the pages gives an answer.
+fn format_pages(pages: Vec<(String, Answer)>, visited: &[String], conf: &Config) -> Result<String> {
+    let mut pages: Vec<(String, Answer)> = pages
+        .into_iter()
+        .filter(|(_, answer)| answer.answer_text.is_some())
+        .collect();
+    if pages.is_empty() {
+        return Err(no_answer(visited));
+    }
+    // answers from several sites are ranked together, by votes of their best answer.
+    if conf.sites().len() > 1 {
+        pages.sort_by_key(|(_, answer)| Reverse(answer.score.unwrap_or(0)));
+    }
+    Ok(format_answers(pages, conf))
+}
+
+/// Make the error that none of the visited question links gives an answer.
+fn no_answer(visited: &[String]) -> Error {
+    let question_links: Vec<String> = visited
+        .iter()
+        .filter(|link| is_question_link(link))
+        .cloned()
+        .collect();
+    Error::NoAnswer(question_links)
+}
 
-                let page: String = get_page(&link, &client, records_cache).await?;
-                let title: String = format!("- Answer from {}", link);
-                let answer: Option<String> = parse_answer(page, &conf);
-                match answer {
-                    Some(content) => results.push(format!("{}\n{}", title, content)),
-                    None => results.push(format!("Can't get answer from {}", link)),
+/// Fetch question pages of the links, and visit each page which gives an
+/// answer with its answer in the order of links.
+///
+/// The first `conf.numbers()` links are fetched concurrently.  The question
+/// without answer doesn't count, so the following links are fetched until
+/// `conf.numbers()` pages are visited or links run out, fast path doesn't wait
+/// for them.
+///
+/// # Returns
+///
+/// `Error::NoAnswer` if none of the links gives an answer.  When a page can't
+/// be fetched after some pages are visited, the error is logged and the
+/// visited pages are kept.
+async fn visit_answers<F: FnMut(String, Answer)>(
+    links: &[String],
+    conf: &Config,
+    records_cache: &mut AnswerRecordsCache,
+    client: &Client,
+    mut visit: F,
+) -> Result<()> {
+    let numbers: usize = conf.numbers() as usize;
+    let mut answered: usize = 0;
+    let mut visited: usize = 0;
+    while answered < numbers && visited < links.len() && (visited == 0 || !conf.fast_path()) {
+        let batch_end: usize = (visited + numbers - answered).min(links.len());
+        let fetched: Result<()> = visit_pages(
+            &links[visited..batch_end],
+            conf,
+            records_cache,
+            client,
+            |page, answer| {
+                if answer.answer_text.is_some() {
+                    answered += 1;
+                    visit(page, answer);
                 }
+            },
+        )
+        .await;
+        visited = batch_end;
+        if let Err(err) = fetched {
+            if answered == 0 {
+                return Err(err);
             }
+            warn!("Can't fetch more question pages, error msg: {:?}", err);
+            break;
+        }
+    }
+    if answered == 0 {
+        return Err(no_answer(&links[..visited]));
+    }
+    Ok(())
+}
+
+/// Fetch question pages of the links concurrently, and visit each page with
+/// its answer in the order of links, as soon as the page and the pages before
+/// it are fetched.
+async fn visit_pages<F: FnMut(String, Answer)>(
+    links: &[String],
+    conf: &Config,
+    records_cache: &mut AnswerRecordsCache,
+    client: &Client,
+    mut visit: F,
+) -> Result<()> {
+    // the given links may contains the url which isn't a question, like tag
+    // pages of the site, just deal with nothing to it.
+    let question_links: Vec<&String> = links.iter().filter(|link| is_question_link(link)).collect();
+    // fetch all pages concurrently, `FuturesOrdered` keeps the order of links.
+    let mut fetching: FuturesOrdered<_> = question_links
+        .iter()
+        .map(|link| {
+            let cached: Option<String> = records_cache.get(link).cloned();
+            get_answer(link, cached, client, conf)
+        })
+        .collect();
+
+    for link in question_links {
+        let fetched: Option<Result<(String, Answer, bool)>> = fetching.next().await;
+        let (page, answer, fresh) = match fetched {
+            Some(fetched) => fetched?,
             None => break,
+        };
+        match &answer.answer_text {
+            Some(_) => info!("Parsed answer from {}", link),
+            None => info!("Can't parse answer from {}", link),
+        }
+        if fresh {
+            records_cache.put(link.to_string(), page.clone());
+        }
+        if let Some(dir) = conf.save_html_dir() {
+            if let Err(err) = save_html(dir, link, &page) {
+                warn!(
+                    "Can't save page of {} into {:?}, error msg: {:?}",
+                    link, dir, err
+                );
+            }
+        }
+        visit(page, answer);
+    }
+    Ok(())
+}
+
+/// Format answers of question pages as output, according to user config.
+fn format_answers(pages: Vec<(String, Answer)>, conf: &Config) -> String {
+    if let OutputOption::Json = conf.option() {
+        let answers: Vec<Answer> = pages.into_iter().map(|(_, answer)| answer).collect();
+        return serde_json::to_string_pretty(&answers).expect(
+            "Answers should be serialized :(  If you see this message, please fire an issue.",
+        );
+    }
+    pages
+        .iter()
+        .enumerate()
+        .map(|(index, (page, answer))| format_answer(page, answer, index == 0, conf))
+        .collect::<Vec<String>>()
+        .join(conf.splitter())
+}
+
+/// Format answer of the question page as text output.
+///
+/// Related questions are only listed under the top answer, if user wants them.
+fn format_answer(page: &str, answer: &Answer, is_top: bool, conf: &Config) -> String {
+    let mut result: String = answer.to_text(conf);
+    if conf.explain_ranking() {
+        result = format!("{}\n{}", result, explain_ranking(page, conf));
+    }
+    if conf.show_related() && is_top {
+        let related: Vec<(String, String)> = related_questions(page, &answer.link);
+        if !related.is_empty() {
+            result = format!("{}\n{}", result, format_related(&related));
+        }
+    }
+    result
+}
+
+/// Get the question page and parse answer from it.
+///
+/// The cached page is used if there is one, and it's fetched again when the
+/// answer can't be parsed from it, because the cached page may be saved when
+/// stackoverflow's markup was different.  The cached page is kept if it can't
+/// be fetched again.
+///
+/// # Returns
+///
+/// The page, the answer parsed from it, and if the page should be put into cache.
+async fn get_answer(
+    link: &str,
+    cached: Option<String>,
+    client: &Client,
+    conf: &Config,
+) -> Result<(String, Answer, bool)> {
+    let page: String = match cached {
+        Some(page) => {
+            info!("Use cached page of {}", link);
+            page
+        }
+        None => {
+            info!("Fetch question page {}", link);
+            let page: String = fetch_question_page(link, client, conf).await?;
+            let answer: Answer = parse_question(link, &page, conf);
+            return Ok((page, answer, true));
+        }
+    };
+    let answer: Answer = parse_question(link, &page, conf);
+    if answer.answer_text.is_none() && !conf.fast_path() {
+        debug!("Can't parse cached page of {}, fetch it again.", link);
+        match fetch_question_page(link, client, conf).await {
+            Ok(fetched) => {
+                let answer: Answer = parse_question(link, &fetched, conf);
+                let fresh: bool = answer.answer_text.is_some();
+                return Ok((fetched, answer, fresh));
+            }
+            // the cached page is still usable, like it's a question without answer.
+            Err(err) => warn!("Can't fetch {} again, use cached page: {:?}", link, err),
         }
     }
-    Ok(results.join(SPLITTER))
+    Ok((page, answer, false))
 }
 
 async fn get_page(
     link: &str,
     client: &Client,
     records_cache: &mut AnswerRecordsCache,
+    conf: &Config,
 ) -> Result<String> {
     // Firstly try to get link from cache.
     let page_from_cache: Option<&String> = records_cache.get(link);
@@ -160,361 +939,3610 @@ async fn get_page(
         Some(page) => Ok(page.to_string()),
         // When we can't get answer from cache, we should get page from network.
         None => {
-            let resp: Response = client
-                .get(link)
-                .header(reqwest::header::USER_AGENT, random_agent())
-                .send()
-                .await?;
-            debug!("Response status from stackoverflow: {:?}", resp);
-            let page: String = resp.text().await?;
+            let page: String = fetch_question_page(link, client, conf).await?;
             records_cache.put(link.to_string(), page.to_string());
             Ok(page)
         }
     }
 }
 
-fn parse_answer(page: String, config: &Config) -> Option<String> {
-    let doc: Document = Document::from(page.as_str());
-    // The question tags may contains useful information about the language topic
-    // so syntect can use correct Syntex reference.
-    let question_tags: Vec<String> = doc
-        .find(Class("post-tag"))
-        .map(|tag_node| tag_node.text())
-        .collect();
+/// Fetch question page from network, according to user config.
+async fn fetch_question_page(link: &str, client: &Client, conf: &Config) -> Result<String> {
+    if conf.fast_path() {
+        let request: RequestBuilder = client
+            .get(link)
+            .header(reqwest::header::USER_AGENT, random_agent())
+            .timeout(FAST_PATH_TIMEOUT);
+        let resp: Response = send_with_retry(request, conf).await?;
+        return read_page(link, resp).await;
+    }
+    // cookies which are set by the page, they're sent again with consent cookie.
+    let (page, mut cookies): (String, Vec<String>) = if conf.wayback_fallback() {
+        (
+            fetch_page_with_wayback(link, client, conf, WAYBACK_PREFIX).await?,
+            vec![],
+        )
+    } else {
+        let request: RequestBuilder = client
+            .get(link)
+            .header(reqwest::header::USER_AGENT, random_agent());
+        let resp: Response = send_with_retry(request, conf).await?;
+        let cookies: Vec<String> = resp
+            .cookies()
+            .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
+            .collect();
+        (read_page(link, resp).await?, cookies)
+    };
+    if !is_consent_gated(&page) {
+        return Ok(page);
+    }
+    if !conf.accept_consent() {
+        return Err(Error::from_parse(
+            "StackOverflow asks for cookie consent before showing answers",
+        ));
+    }
+    debug!("Page of {} is gated by cookie consent, accept it.", link);
+    // reqwest can't put a cookie into the cookie store of client, and the store
+    // is skipped when request has its own `Cookie` header, so the cookies got
+    // by the gated page are merged into the header to keep the session.
+    cookies.push(CONSENT_COOKIE.to_string());
+    let request: RequestBuilder = client
+        .get(link)
+        .header(reqwest::header::USER_AGENT, random_agent())
+        .header(reqwest::header::COOKIE, cookies.join("; "));
+    let resp: Response = send_with_retry(request, conf).await?;
+    read_page(link, resp).await
+}
+
+/// Read page from the response, the status and size of page are logged.
+async fn read_page(link: &str, resp: Response) -> Result<String> {
+    let status: StatusCode = resp.status();
+    let page: String = resp.text().await?;
+    info!("Got {} from {}, {} bytes", status, link, page.len());
+    Ok(page)
+}
+
+/// Check if stackoverflow gives a cookie consent banner rather than answers,
+/// which happens for EU users.
+fn is_consent_gated(page: &str) -> bool {
+    !page.contains("class=\"answer") && CONSENT_MARKERS.iter().any(|marker| page.contains(marker))
+}
+
+/// Fetch page from network, when the live page can't be fetched, try the
+/// snapshot on Wayback Machine.
+///
+/// Note that the archived page may have older stackoverflow markup.
+///
+/// # Arguments
+///
+/// * `link` - link to the question page.
+/// * `client` - An instance of `request::Client` object which can use to fire http request.
+/// * `conf` - User config, which decides how requests are retried.
+/// * `wayback_prefix` - The prefix to make Wayback Machine url from the link.
+async fn fetch_page_with_wayback(
+    link: &str,
+    client: &Client,
+    conf: &Config,
+    wayback_prefix: &str,
+) -> Result<String> {
+    let request: RequestBuilder = client
+        .get(link)
+        .header(reqwest::header::USER_AGENT, random_agent());
+    let live: Result<Response> = send_with_retry(request, conf).await;
+    match live {
+        Ok(resp) if !resp.status().is_server_error() => return read_page(link, resp).await,
+        Ok(resp) => warn!("Get {} failed with status {}", link, resp.status()),
+        Err(err) => warn!("Get {} failed: {}", link, err),
+    }
+    let archived_link: String = format!("{}{}", wayback_prefix, link);
+    debug!("Try to get page from Wayback Machine {}", archived_link);
+    fetch_page(&archived_link, client, conf).await
+}
+
+/// Save question page into the given directory.
+///
+/// The page is saved as `<question id>.html`, and the directory is created if
+/// it's not existed.
+fn save_html(dir: &Path, link: &str, page: &str) -> Result<()> {
+    let id: String = question_id(link).ok_or(Error::from_parse("Can't find question id"))?;
+    create_dir_all(dir)?;
+    fs::write(dir.join(format!("{}.html", id)), page)?;
+    Ok(())
+}
+
+/// Fetch page from network directly, without touching the cache.
+async fn fetch_page(link: &str, client: &Client, conf: &Config) -> Result<String> {
+    let request: RequestBuilder = client
+        .get(link)
+        .header(reqwest::header::USER_AGENT, random_agent());
+    let resp: Response = send_with_retry(request, conf).await?;
+    debug!("Response status from stackoverflow: {:?}", resp);
+    read_page(link, resp).await
+}
+
+/// Answer of a question, which is parsed from the question page.
+#[derive(Serialize, Debug)]
+struct Answer {
+    link: String,
+    /// Title of the question.
+    title: String,
+    question_tags: Vec<String>,
+    /// When the question is asked, like `2011-10-14`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    asked: Option<String>,
+    /// Votes of the best selected answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<i16>,
+    /// Whether the best selected answer is accepted by the asker.
+    accepted: bool,
+    /// Reputation of the author of the best selected answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reputation: Option<u32>,
+    /// Content of the selected answers, or None if we can't get answer.
+    answer_text: Option<String>,
+}
+
+impl Answer {
+    /// Format the answer as text output.
+    ///
+    /// The tags and ask date of question are appended to the title line if
+    /// `conf.show_question_meta()` is true, like
+    /// `- Answer from <link> [tags: rust, json] [asked: 2011-10-14]`.  The
+    /// content is truncated if user limits lines or characters of answer.  The
+    /// title line is omitted if `conf.show_header()` is false, or the link of
+    /// question is unknown.
+    fn to_text(&self, conf: &Config) -> String {
+        let content: &str = match &self.answer_text {
+            Some(content) => content,
+            None => return format!("Can't get answer from {}", self.link),
+        };
+        let truncated: Option<String> =
+            truncate_answer(content, conf.max_answer_lines(), conf.max_answer_chars());
+        let content: String = match truncated {
+            Some(truncated) => format!("{}\n… (truncated, see {})", truncated, self.link),
+            None => content.to_string(),
+        };
+        if !conf.show_header() || self.link.is_empty() {
+            return content;
+        }
+        let mut title: String = format!("- Answer from {}", self.link);
+        if conf.show_question_meta() {
+            if !self.question_tags.is_empty() {
+                title = format!("{} [tags: {}]", title, self.question_tags.join(", "));
+            }
+            if let Some(asked) = &self.asked {
+                title = format!("{} [asked: {}]", title, asked);
+            }
+        }
+        format!("{}\n{}", title, content)
+    }
+}
+
+/// Parse the question page into answer.
+///
+/// # Arguments
+///
+/// * `link` - link to the question page.
+/// * `page` - the question page.
+/// * `config` - user config, which decides how the answer is selected and formatted.
+fn parse_question(link: &str, page: &str, config: &Config) -> Answer {
+    let doc: Document = if config.bounded_parse() {
+        Document::from(bound_page(page).as_str())
+    } else {
+        Document::from(page)
+    };
+    // The question tags may contains useful information about the language topic
+    // so syntect can use correct Syntex reference.
+    let question_tags: Vec<String> = doc
+        .find(Class("post-tag"))
+        .map(|tag_node| tag_node.text())
+        .collect();
+    let title: String = match doc
+        .find(Attr("id", "question-header").descendant(Name("h1")))
+        .next()
+    {
+        Some(header) => header.text().trim().to_string(),
+        None => Url::parse(link)
+            .ok()
+            .and_then(|url| extract_question(url.path()))
+            .unwrap_or_default(),
+    };
+
+    let candidates: Vec<Candidate> = rank_answers(&doc, config);
+    // old answers are only kept when none of answers is new enough.
+    let outdated: Option<u16> = config.edited_after().filter(|year| {
+        candidates
+            .first()
+            .is_some_and(|candidate| !is_edited_after(candidate.node, *year))
+    });
+    let selected: Vec<(Candidate, String)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            answer_content(candidate.node, &question_tags, config)
+                .map(|content| (candidate, content))
+        })
+        .take(config.answers_per_question() as usize)
+        .collect();
+    let (score, accepted, author): (Option<i16>, bool, Option<(String, u32)>) =
+        match selected.first() {
+            Some((best, _)) => (
+                vote_count(best.node),
+                best.accepted,
+                answer_author(best.node),
+            ),
+            None => (None, false, None),
+        };
+    let answers: Vec<String> = selected.into_iter().map(|(_, content)| content).collect();
+    let answer_text: Option<String> = if answers.is_empty() {
+        None
+    } else {
+        let answers: String = answers.join(ANSWER_SPLITTER);
+        match outdated {
+            // code is output as it is.
+            Some(year) if !matches!(config.option(), OutputOption::OnlyCode) => Some(format!(
+                "Note: no answer is edited in or after {}, showing the best one.\n{}",
+                year, answers
+            )),
+            _ => Some(answers),
+        }
+    };
+    Answer {
+        link: link.to_string(),
+        title,
+        question_tags,
+        asked: question_asked(&doc),
+        score,
+        accepted,
+        reputation: author.map(|(_, reputation)| reputation),
+        answer_text,
+    }
+}
 
-    let appropriate_answer = select_answer(&doc);
+/// Get the date when the question is asked, like `2011-10-14`.
+fn question_asked(doc: &Document) -> Option<String> {
+    doc.find(Name("time").and(Attr("itemprop", "dateCreated")))
+        .next()
+        .and_then(|time| time.attr("datetime"))
+        .filter(|datetime| parse_day(datetime).is_some())
+        .and_then(|datetime| datetime.get(..10))
+        .map(String::from)
+}
 
-    if let Some(answer) = appropriate_answer {
-        match *config.option() {
+/// Get content of the given answer according to user config.
+fn answer_content(answer: Node, question_tags: &[String], config: &Config) -> Option<String> {
+    let content: String = match image_only_note(answer) {
+        Some(note) => note,
+        None => match *config.option() {
             OutputOption::OnlyCode => {
-                return parse_answer_instruction(answer, question_tags, config.colorize());
+                let code: Option<String> =
+                    parse_answer_instruction(answer, question_tags.to_vec(), config);
+                if code.is_none() && config.code_mode_prose_fallback() {
+                    parse_answer_first_paragraph(answer, config)?
+                } else {
+                    code?
+                }
             }
-            OutputOption::All => {
-                return parse_answer_detailed(answer, question_tags, config.colorize());
+            OutputOption::All | OutputOption::Json => {
+                parse_answer_detailed(answer, question_tags.to_vec(), config)?
             }
+            OutputOption::Markdown => parse_answer_markdown(answer, question_tags, config)?,
             _ => panic!(
                 "parse_answer shoudn't get config with OutputOption::Link.\n
                 If you get this message, please fire an issue"
             ),
+        },
+    };
+    let mut content: String = content;
+    if config.show_reputation() {
+        if let Some((author, reputation)) = answer_author(answer) {
+            content = format!(
+                "Answered by {} (reputation {})\n{}",
+                author, reputation, content
+            );
+        }
+    }
+    // score is only shown with answer details, code is output as it is.
+    if config.show_score()
+        && config.show_header()
+        && !matches!(config.option(), OutputOption::OnlyCode)
+    {
+        if let Some(header) = score_header(answer) {
+            content = format!("{}\n{}", header, content);
+        }
+    }
+    Some(content)
+}
+
+/// Make header like `[score: 142] [accepted]` of the answer.
+///
+/// # Returns
+///
+/// The header, or None if the answer is neither scored nor accepted.
+fn score_header(answer: Node) -> Option<String> {
+    let mut header: Vec<String> = vec![];
+    if let Some(votes) = vote_count(answer) {
+        header.push(format!("[score: {}]", votes));
+    }
+    if is_accepted(answer) {
+        header.push(String::from("[accepted]"));
+    }
+    if header.is_empty() {
+        return None;
+    }
+    Some(header.join(" "))
+}
+
+/// Remove the parts of question page which hors doesn't need.
+///
+/// Building `Document` is memory-heavy for huge question page, so the page
+/// is cut off at sidebar or footer (which follows the answers), and all
+/// `<script>` and `<style>` elements are removed before parsing.
+fn bound_page(page: &str) -> String {
+    const END_MARKERS: [&str; 2] = ["<div id=\"sidebar\"", "<footer"];
+    let end: usize = END_MARKERS
+        .iter()
+        .filter_map(|marker| page.find(marker))
+        .min()
+        .unwrap_or(page.len());
+    let mut rest: &str = &page[..end];
+
+    let mut bounded: String = String::new();
+    loop {
+        let next_element: Option<(usize, &str)> = ["<script", "<style"]
+            .iter()
+            .filter_map(|tag| rest.find(tag).map(|start| (start, *tag)))
+            .min();
+        match next_element {
+            Some((start, tag)) => {
+                bounded.push_str(&rest[..start]);
+                let close_tag: String = format!("</{}>", &tag[1..]);
+                match rest[start..].find(&close_tag) {
+                    Some(close) => rest = &rest[start + close + close_tag.len()..],
+                    None => break,
+                }
+            }
+            None => {
+                bounded.push_str(rest);
+                break;
+            }
+        }
+    }
+    bounded
+}
+
+/// Candidate answer, with the information which is used to rank it.
+struct Candidate<'a> {
+    node: Node<'a>,
+    votes: i16,
+    accepted: bool,
+    recency: f32,
+    language_match: bool,
+    score: f32,
+}
+
+/// Rank answers by most voted.
+///
+/// The answer which doesn't meet user config is ignored.  When user gives
+/// recency weight, the score of answer is blended by it's votes and how recent
+/// it is, both of them are normalized into `[0, 1]` among candidate answers.
+/// With `Rank::LanguageThenVotes`, answers whose code is written in user's
+/// language are always ranked before others.  When user gives
+/// `Config::edited_after`, older answers are dropped if any answer is new
+/// enough.
+///
+/// # Returns
+///
+/// Candidate answers, the best one goes first.
+fn rank_answers<'a>(doc: &'a Document, config: &Config) -> Vec<Candidate<'a>> {
+    let answers: Vec<(Node, i16)> = doc
+        .find(Class("answer"))
+        .filter(|answer| skip_reason(*answer, config).is_none())
+        .map(|answer| (answer, answer_votes(answer)))
+        .collect();
+
+    let max_voted: f32 = match answers.iter().map(|(_, voted)| *voted).max() {
+        Some(max_voted) => max_voted.max(1) as f32,
+        None => return vec![],
+    };
+    let days: Vec<Option<i64>> = answers
+        .iter()
+        .map(|(answer, _)| answer_last_active_day(*answer))
+        .collect();
+    let newest: i64 = days.iter().flatten().copied().max().unwrap_or(0);
+    let oldest: i64 = days.iter().flatten().copied().min().unwrap_or(0);
+    let language: Option<String> = match config.rank() {
+        Rank::LanguageThenVotes => config.language().map(str::to_lowercase),
+        Rank::Votes => None,
+    };
+
+    let mut candidates: Vec<Candidate> = answers
+        .into_iter()
+        .zip(days)
+        .map(|((node, votes), day)| {
+            let recency: f32 = match day {
+                Some(day) if newest > oldest => (day - oldest) as f32 / (newest - oldest) as f32,
+                _ => 0.0,
+            };
+            let language_match: bool = match &language {
+                Some(language) => answer_languages(node).contains(language),
+                None => false,
+            };
+            let score: f32 =
+                config.vote_weight() * votes as f32 / max_voted + config.recency_weight() * recency;
+            let candidate: Candidate = Candidate {
+                node,
+                votes,
+                accepted: is_accepted(node),
+                recency,
+                language_match,
+                score,
+            };
+            debug!(
+                "Answer score {} (votes: {}, recency: {}, language match: {})",
+                candidate.score, candidate.votes, candidate.recency, candidate.language_match
+            );
+            candidate
+        })
+        .collect();
+    // answers order in page depends on the active sorting tab, so don't rely on it.
+    // The sort is stable, the former answer only wins when everything is equal.
+    let prefer_accepted: bool = config.prefer_accepted();
+    candidates.sort_by(|a, b| {
+        let accepted_first: Ordering = if prefer_accepted {
+            b.accepted.cmp(&a.accepted)
+        } else {
+            Ordering::Equal
+        };
+        accepted_first
+            .then(b.language_match.cmp(&a.language_match))
+            .then(b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal))
+            .then(b.accepted.cmp(&a.accepted))
+    });
+    if let Some(year) = config.edited_after() {
+        if candidates
+            .iter()
+            .any(|candidate| is_edited_after(candidate.node, year))
+        {
+            candidates.retain(|candidate| is_edited_after(candidate.node, year));
+        }
+    }
+    candidates
+}
+
+/// Check if the answer should be ignored when ranking.
+///
+/// # Returns
+///
+/// The reason why the answer is ignored, or None if it's a candidate.
+fn skip_reason(answer: Node, config: &Config) -> Option<&'static str> {
+    // screenshot of code is useless when user only wants code, so just skip it.
+    if matches!(config.option(), OutputOption::OnlyCode) && image_only_note(answer).is_some() {
+        return Some("it only contains screenshots");
+    }
+    if let Some(keyword) = config.answer_must_contain() {
+        let body: String = find_answer_bodies(answer)
+            .iter()
+            .map(|body| body.text().to_lowercase())
+            .collect();
+        if !body.contains(&keyword.to_lowercase()) {
+            return Some("it doesn't contain the keyword");
         }
     }
+    // accepted answer is still useful if user prefers it.
+    if answer_votes(answer) < config.min_score()
+        && !(config.prefer_accepted() && is_accepted(answer))
+    {
+        return Some("its score is below the minimum");
+    }
     None
 }
 
-/// Select answer by most voted.
-fn select_answer(doc: &Document) -> Option<Node> {
-    let mut selected_node: Option<Node> = None;
-    let mut selected_voted: i16 = 0;
-    let answers = doc.find(Class("answer"));
+/// Find "Linked" and "Related" questions in the sidebar of question page.
+///
+/// # Arguments
+///
+/// * `page` - the question page.
+/// * `link` - link to the question page, which is used to resolve relative links.
+///
+/// # Returns
+///
+/// Title and absolute link of each question, duplicate questions are removed.
+fn related_questions(page: &str, link: &str) -> Vec<(String, String)> {
+    let base: Url = match Url::parse(link) {
+        Ok(url) => url,
+        Err(_) => return vec![],
+    };
+    let doc: Document = Document::from(page);
+    let anchors = doc.find(
+        Class("linked")
+            .or(Class("related"))
+            .descendant(Name("a").and(Class("question-hyperlink"))),
+    );
+    let mut questions: Vec<(String, String)> = vec![];
+    for anchor in anchors {
+        let url: Url = match anchor.attr("href").map(|href| base.join(href)) {
+            Some(Ok(url)) => url,
+            _ => continue,
+        };
+        let url: String = url.to_string();
+        if questions.iter().all(|(_, existed)| existed != &url) {
+            questions.push((anchor.text().trim().to_string(), url));
+        }
+    }
+    questions
+}
+
+/// Format related questions as a list under `Related questions:` header.
+fn format_related(related: &[(String, String)]) -> String {
+    let questions: Vec<String> = related
+        .iter()
+        .map(|(title, url)| format!("- {}\n  {}", title, url))
+        .collect();
+    format!("Related questions:\n{}", questions.join("\n"))
+}
 
-    for answer in answers {
-        // fetch vote count to know which answer is best for users.
-        let voted: Node = answer.find(Class("js-vote-count")).next().expect(
-            "Can't find vote information :(  If you see this message, please fire an issue.",
-        );
-        debug!("Voted node infromation {:?}", voted);
-        // Hors think that the voted number should less than 32767, so make it i16 type.
-        let voted: i16 = voted
-            .text()
-            .trim()
-            .parse()
-            .expect("Vote information should be a number :(  If you see this message, please fire an issue.˝");
-        if selected_voted < voted {
-            selected_voted = voted;
-            selected_node = Some(answer);
+/// Explain how answers in the page are ranked, it's useful when hors selects
+/// surprising answer.
+///
+/// # Returns
+///
+/// One line for each answer in page order, which contains its score components
+/// and why it was or wasn't chosen.
+fn explain_ranking(page: &str, config: &Config) -> String {
+    let doc: Document = Document::from(page);
+    let candidates: Vec<Candidate> = rank_answers(&doc, config);
+    let mut lines: Vec<String> = vec![String::from("Ranking of answers:")];
+    for (i, answer) in doc.find(Class("answer")).enumerate() {
+        let name: String = match answer.attr("data-answerid") {
+            Some(id) => format!("answer #{} (id {})", i + 1, id),
+            None => format!("answer #{}", i + 1),
+        };
+        if let Some(reason) = skip_reason(answer, config) {
+            lines.push(format!("  {}: skipped, because {}", name, reason));
+            continue;
+        }
+        let position: usize = match candidates
+            .iter()
+            .position(|candidate| candidate.node.index() == answer.index())
+        {
+            Some(position) => position,
+            None => {
+                lines.push(format!(
+                    "  {}: skipped, because it's edited before {}",
+                    name,
+                    config.edited_after().unwrap_or_default()
+                ));
+                continue;
+            }
+        };
+        let candidate: &Candidate = &candidates[position];
+        let verdict: String = if position == 0 {
+            String::from("chosen")
+        } else {
+            format!("not chosen, ranked #{}", position + 1)
+        };
+        lines.push(format!(
+            "  {}: {}, score {:.2} (votes: {}, accepted: {}, recency: {:.2}, language match: {})",
+            name,
+            verdict,
+            candidate.score,
+            candidate.votes,
+            candidate.accepted,
+            candidate.recency,
+            candidate.language_match
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Get languages of code in the answer, which are marked by stackoverflow
+/// as `lang-xxx` class of `pre`, or `language-xxx` class of `code`.
+fn answer_languages(answer: Node) -> Vec<String> {
+    answer
+        .find(Name("pre").or(Name("code")))
+        .filter_map(|node| node.attr("class"))
+        .flat_map(|class| class.split_whitespace())
+        .filter_map(|class| {
+            class
+                .strip_prefix("lang-")
+                .or_else(|| class.strip_prefix("language-"))
+        })
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Check if the answer is accepted by the asker, stackoverflow marks it by
+/// `accepted-answer` class, or `accepted-answer` id in older pages.
+fn is_accepted(answer: Node) -> bool {
+    answer.is(Class("accepted-answer")) || answer.attr("id") == Some("accepted-answer")
+}
+
+/// Get vote count of the answer, it's zero if vote count is unavailable.
+fn answer_votes(answer: Node) -> i16 {
+    vote_count(answer).unwrap_or(0)
+}
+
+/// Get vote count of the answer, or None if it's unavailable.
+fn vote_count(answer: Node) -> Option<i16> {
+    // fetch vote count to know which answer is best for users.
+    let voted: Node = match answer.find(Class("js-vote-count")).next() {
+        Some(voted) => voted,
+        None => {
+            debug!("Can't find vote information.");
+            return None;
+        }
+    };
+    debug!("Voted node infromation {:?}", voted);
+    // `data-value` contains the exact number, while the text may be abbreviated.
+    let count: String = match voted.attr("data-value") {
+        Some(value) => value.to_string(),
+        None => voted.text(),
+    };
+    // Hors think that the voted number should less than 32767, so make it i16 type.
+    match count.trim().parse() {
+        Ok(count) => Some(count),
+        Err(_) => {
+            debug!("Vote information {:?} is not a number.", count);
+            None
         }
     }
-    selected_node
+}
+
+/// Get the author of answer and their reputation.
+///
+/// The answer may be edited by others, the author signature goes last.
+fn answer_author(answer: Node) -> Option<(String, u32)> {
+    let author: Node = answer.find(Class("user-info")).last()?;
+    let name: String = author
+        .find(Class("user-details").descendant(Name("a")))
+        .next()?
+        .text()
+        .trim()
+        .to_string();
+    let reputation: u32 = parse_count(&author.find(Class("reputation-score")).next()?.text())?;
+    Some((name, reputation))
+}
+
+/// Parse count which is displayed by stackoverflow, large count is abbreviated
+/// like `12.3k` or `1.2m`, and it may contain commas like `1,234`.
+fn parse_count(text: &str) -> Option<u32> {
+    let text: String = text.trim().replace(',', "").to_lowercase();
+    let (number, unit): (&str, f64) = if let Some(number) = text.strip_suffix('k') {
+        (number, 1_000.0)
+    } else if let Some(number) = text.strip_suffix('m') {
+        (number, 1_000_000.0)
+    } else {
+        (text.as_str(), 1.0)
+    };
+    let count: f64 = number.parse::<f64>().ok()? * unit;
+    if count < 0.0 {
+        return None;
+    }
+    Some(count.round() as u32)
+}
+
+/// Get the day (since unix epoch) when the answer is answered or edited lastly.
+fn answer_last_active_day(answer: Node) -> Option<i64> {
+    let relative_times = answer
+        .find(Class("relativetime"))
+        .filter_map(|node| node.attr("title"));
+    let times = answer
+        .find(Name("time"))
+        .filter_map(|node| node.attr("datetime"));
+    relative_times.chain(times).filter_map(parse_day).max()
+}
+
+/// Check if the answer is answered or edited in or after the year, the times
+/// are read from `.user-action-time` elements.
+fn is_edited_after(answer: Node, year: u16) -> bool {
+    answer
+        .find(Class("user-action-time"))
+        .flat_map(|action| {
+            let relative_times = action
+                .find(Class("relativetime"))
+                .filter_map(|node| node.attr("title"));
+            let times = action
+                .find(Name("time"))
+                .filter_map(|node| node.attr("datetime"));
+            relative_times.chain(times).collect::<Vec<&str>>()
+        })
+        .filter(|time| parse_day(time).is_some())
+        .filter_map(|time| time.get(..4)?.parse::<u16>().ok())
+        .any(|edited| edited >= year)
+}
+
+/// Parse date like `2011-10-14 16:58:14Z` into days since unix epoch.
+fn parse_day(time: &str) -> Option<i64> {
+    let date: &str = time.get(..10)?;
+    let mut parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // days from civil algorithm, see http://howardhinnant.github.io/date_algorithms.html
+    let year: i64 = if month <= 2 { year - 1 } else { year };
+    let era: i64 = year.div_euclid(400);
+    let year_of_era: i64 = year - era * 400;
+    let day_of_year: i64 = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era: i64 = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Find the body of answer, stackoverflow uses `s-prose` for newer pages.
+fn find_answer_body(answer_node: Node) -> Option<Node> {
+    answer_node
+        .find(Class("post-text"))
+        .next()
+        .or_else(|| answer_node.find(Class("s-prose")).next())
+}
+
+/// Find all bodies of answer in page order, some answers are split into
+/// several `post-text` blocks, e.g: the "Update" sections.
+fn find_answer_bodies(answer_node: Node) -> Vec<Node> {
+    let bodies: Vec<Node> = answer_node.find(Class("post-text")).collect();
+    if bodies.is_empty() {
+        answer_node.find(Class("s-prose")).collect()
+    } else {
+        bodies
+    }
+}
+
+/// Check if the answer is just screenshots, without any text.
+///
+/// # Returns
+///
+/// A note which contains image urls if the answer only contains images,
+/// else returns None.
+fn image_only_note(answer_node: Node) -> Option<String> {
+    let body: Node = find_answer_body(answer_node)?;
+    if !body.text().trim().is_empty() {
+        return None;
+    }
+    let images: Vec<&str> = body
+        .find(Name("img"))
+        .filter_map(|img| img.attr("src"))
+        .collect();
+    if images.is_empty() {
+        return None;
+    }
+    Some(format!("This answer is a screenshot: {}", images.join(" ")))
 }
 
 fn parse_answer_instruction(
     answer_node: select::node::Node,
     question_tags: Vec<String>,
-    should_colorize: bool,
+    config: &Config,
 ) -> Option<String> {
     let code_elements: [&str; 2] = ["pre", "code"];
     for code_element in &code_elements {
         if let Some(title) = answer_node.find(Name(*code_element)).next() {
-            if should_colorize {
-                return Some(colorized_code(title.text(), &question_tags));
+            let code: String = if config.colorize() {
+                colorized_code(
+                    code_text(title, config),
+                    &question_tags,
+                    syntax_set(config),
+                    config.highlight_language(),
+                    theme(config),
+                    config.color_depth(),
+                )
             } else {
-                return Some(title.text());
+                code_text(title, config)
+            };
+            if config.code_line_numbers() {
+                return Some(number_lines(&code, config.colorize()));
             }
+            return Some(code);
         }
     }
     None
 }
 
-fn parse_answer_detailed(
-    answer_node: select::node::Node,
-    question_tags: Vec<String>,
-    should_colorize: bool,
-) -> Option<String> {
-    if let Some(instruction) = answer_node.find(Class("post-text")).next() {
-        if !should_colorize {
-            return Some(instruction.text());
-        } else {
-            let mut formatted_answer: String = String::new();
-            for sub_node in instruction.children() {
-                match sub_node.name() {
-                    Some("pre") => formatted_answer
-                        .push_str(&(colorized_code(sub_node.text(), &question_tags) + "\n")),
-                    Some("code") => {
-                        formatted_answer.push_str(&colorized_code(sub_node.text(), &question_tags))
-                    }
-                    Some(_) => formatted_answer.push_str(&(sub_node.text() + "\n\n")),
-                    None => continue,
-                }
-            }
-            return Some(formatted_answer);
-        }
+/// Prefix each line of code with right-aligned line number.
+///
+/// The line numbers are never colorized.  Escaped lines of colorized code
+/// don't end with a reset, so the color is reset before each line number.
+fn number_lines(code: &str, colorized: bool) -> String {
+    let lines: Vec<&str> = code.lines().collect();
+    let width: usize = lines.len().to_string().len();
+    let reset: &str = if colorized { "\x1b[0m" } else { "" };
+    let mut numbered: String =
+        String::with_capacity(code.len() + lines.len() * (width + 3 + reset.len()));
+    for (index, line) in lines.iter().enumerate() {
+        numbered.push_str(&format!(
+            "{}{:>width$} | {}\n",
+            reset,
+            index + 1,
+            line,
+            width = width
+        ));
+    }
+    // the color of last line doesn't leak into the following output.
+    if colorized && numbered.ends_with('\n') {
+        numbered.insert_str(numbered.len() - 1, reset);
+    }
+    numbered
+}
+
+/// Get the first paragraph of answer, it's useful for the answer which
+/// doesn't contains any code.
+fn parse_answer_first_paragraph(
+    answer_node: select::node::Node,
+    config: &Config,
+) -> Option<String> {
+    find_answer_body(answer_node)
+        .and_then(|instruction| instruction.find(Name("p")).next())
+        .map(|paragraph| node_text(paragraph, config).trim().to_string())
+}
+
+fn parse_answer_detailed(
+    answer_node: select::node::Node,
+    question_tags: Vec<String>,
+    config: &Config,
+) -> Option<String> {
+    let bodies: Vec<Node> = find_answer_bodies(answer_node);
+    if bodies.is_empty() {
+        return None;
+    }
+    let mut formatted_answer: String = String::new();
+    for body in bodies {
+        if !formatted_answer.is_empty() && !formatted_answer.ends_with('\n') {
+            formatted_answer.push('\n');
+        }
+        formatted_answer.push_str(&format_answer_body(body, &question_tags, config));
+    }
+    if config.show_comments() && matches!(config.option(), OutputOption::All) {
+        if let Some(comments) = top_comments(answer_node, config) {
+            formatted_answer = format!("{}\n{}", formatted_answer.trim_end(), comments);
+        }
+    }
+    Some(formatted_answer)
+}
+
+/// Format one `post-text` block of answer, code is colorized if user wants it.
+fn format_answer_body(body: Node, question_tags: &[String], config: &Config) -> String {
+    if !config.colorize() {
+        return block_text(body, config);
+    }
+    let mut formatted_body: String = String::new();
+    for sub_node in body.children() {
+        match sub_node.name() {
+            Some("pre") => formatted_body.push_str(
+                &(colorized_code(
+                    code_text(sub_node, config),
+                    question_tags,
+                    syntax_set(config),
+                    config.highlight_language(),
+                    theme(config),
+                    config.color_depth(),
+                ) + "\n"),
+            ),
+            Some("code") => formatted_body.push_str(&colorized_code(
+                node_text(sub_node, config),
+                question_tags,
+                syntax_set(config),
+                config.highlight_language(),
+                theme(config),
+                config.color_depth(),
+            )),
+            Some(_) => formatted_body.push_str(&(node_text(sub_node, config) + "\n\n")),
+            None => continue,
+        }
+    }
+    formatted_body
+}
+
+/// Get text of answer body, blocks like paragraphs and code are separated by
+/// line breaks, even if the markup has no whitespace between them.
+fn block_text(body: Node, config: &Config) -> String {
+    let mut text: String = String::new();
+    for child in body.children() {
+        let is_block: bool = matches!(
+            child.name(),
+            Some("p" | "pre" | "ul" | "ol" | "blockquote" | "div" | "table" | "hr")
+                | Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+        );
+        if is_block && !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&node_text(child, config));
+    }
+    text
+}
+
+/// Make the "Comments:" section from the top comments of answer, comments with
+/// higher score go first.
+///
+/// # Returns
+///
+/// The section, or None if the answer has no comments.
+fn top_comments(answer: Node, config: &Config) -> Option<String> {
+    let mut comments: Vec<(i32, String)> = answer
+        .find(Class("comment-copy"))
+        .map(|copy| {
+            (
+                comment_score(copy),
+                node_text(copy, config).trim().to_string(),
+            )
+        })
+        .filter(|(_, text)| !text.is_empty())
+        .collect();
+    if comments.is_empty() {
+        return None;
+    }
+    // stable sort keeps page order of comments with the same score.
+    comments.sort_by_key(|(score, _)| -score);
+    let lines: Vec<String> = comments
+        .into_iter()
+        .take(MAX_COMMENTS)
+        .map(|(_, text)| format!("- {}", text))
+        .collect();
+    Some(format!("Comments:\n{}\n", lines.join("\n")))
+}
+
+/// Get score of the comment, it's zero if the comment isn't upvoted.
+fn comment_score(copy: Node) -> i32 {
+    let mut node: Option<Node> = copy.parent();
+    while let Some(ancestor) = node {
+        if ancestor.is(Class("comment")) {
+            if let Some(score) = ancestor.attr("data-comment-score") {
+                return score.trim().parse().unwrap_or(0);
+            }
+            return ancestor
+                .find(Class("comment-score"))
+                .next()
+                .and_then(|score| score.text().trim().parse().ok())
+                .unwrap_or(0);
+        }
+        node = ancestor.parent();
+    }
+    0
+}
+
+/// Get answer details as markdown, code blocks are fenced with the language
+/// of question.
+fn parse_answer_markdown(
+    answer_node: Node,
+    question_tags: &[String],
+    config: &Config,
+) -> Option<String> {
+    let bodies: Vec<Node> = find_answer_bodies(answer_node);
+    if bodies.is_empty() {
+        return None;
+    }
+    let language: Option<&str> = config.highlight_language().map(syntax_token).or_else(|| {
+        question_tags
+            .iter()
+            .map(|tag| syntax_token(tag))
+            .find(|token| syntax_set(config).find_syntax_by_token(token).is_some())
+    });
+    let markdown: String = bodies
+        .into_iter()
+        .map(|body| to_markdown(body, language))
+        .filter(|markdown| !markdown.is_empty())
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    if config.sanitize_control_chars() {
+        Some(escape_control_chars(&markdown))
+    } else {
+        Some(markdown)
+    }
+}
+
+/// Get text of the node, control characters are escaped if user wants it.
+fn node_text(node: Node, config: &Config) -> String {
+    let text: String = node.text();
+    if config.sanitize_control_chars() {
+        escape_control_chars(&text)
+    } else {
+        text
+    }
+}
+
+/// Get text of code block, UI artifacts like the "Copy" button are dropped, and
+/// blank lines around the code are trimmed.  Blank lines inside the code are
+/// kept.
+fn code_text(code: Node, config: &Config) -> String {
+    let mut text: String = String::new();
+    push_code_text(code, &mut text);
+    let lines: Vec<&str> = text.lines().collect();
+    let first: usize = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let last: usize = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(first, |last| last + 1);
+    let code: String = lines[first..last].join("\n").trim_end().to_string();
+    if config.sanitize_control_chars() {
+        escape_control_chars(&code)
+    } else {
+        code
+    }
+}
+
+fn push_code_text(node: Node, text: &mut String) {
+    const ARTIFACT_CLASSES: [&str; 3] = ["js-copy-button", "copy-button", "s-btn"];
+    if let Some(content) = node.as_text() {
+        text.push_str(content);
+        return;
+    }
+    if node.name() == Some("button") || ARTIFACT_CLASSES.iter().any(|class| node.is(Class(*class)))
+    {
+        return;
+    }
+    for child in node.children() {
+        push_code_text(child, text);
+    }
+}
+
+/// Escape control characters (except tab and newline) as `\u{NN}`,
+/// so they can't mess up the terminal.
+fn escape_control_chars(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_control() && c != '\t' && c != '\n' {
+            escaped.push_str(&c.escape_unicode().to_string());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// make code block colorized.
+///
+/// Note that this function should only accept code block.
+fn colorized_code(
+    code: String,
+    possible_tags: &[String],
+    ss: &SyntaxSet,
+    language: Option<&str>,
+    theme: &Theme,
+    depth: ColorDepth,
+) -> String {
+    let syntax: &SyntaxReference = match language {
+        // the given language is trusted, even if it's unknown.
+        Some(language) => ss
+            .find_syntax_by_token(syntax_token(language))
+            .unwrap_or_else(|| ss.find_syntax_plain_text()),
+        None => guess_syntax(possible_tags, &code, ss),
+    };
+    let mut h = HighlightLines::new(syntax, theme);
+    // escapes make the output several times longer than the code.
+    let mut colorized: String = String::with_capacity(code.len() * 4);
+
+    for line in LinesWithEndings::from(code.as_str()) {
+        let ranges: Vec<(Style, &str)> = h.highlight(line, ss);
+        let escaped: String = match depth {
+            ColorDepth::TrueColor => as_24_bit_terminal_escaped(&ranges, false),
+            ColorDepth::Ansi256 => as_256_color_terminal_escaped(&ranges, false),
+        };
+        colorized.push_str(&escaped);
+    }
+    colorized
+}
+
+/// Guess syntax of the code by question tags, when none of tags resolves,
+/// guess it from the content of code, e.g: shebang line or keywords.
+fn guess_syntax<'a>(
+    possible_tags: &[String],
+    code: &str,
+    ss: &'a SyntaxSet,
+) -> &'a SyntaxReference {
+    for tag in possible_tags {
+        let syntax = ss.find_syntax_by_token(syntax_token(tag));
+        if let Some(result) = syntax {
+            return result;
+        }
+    }
+    code.lines()
+        .next()
+        .and_then(|first_line| ss.find_syntax_by_first_line(first_line))
+        .or_else(|| guess_language(code).and_then(|token| ss.find_syntax_by_token(token)))
+        .unwrap_or_else(|| ss.find_syntax_plain_text())
+}
+
+/// Normalize stackoverflow tag into the token which syntect knows, e.g. `golang` to `go`.
+///
+/// The tag is returned as it is if it doesn't have an alias.
+fn syntax_token(tag: &str) -> &str {
+    SYNTAX_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == tag)
+        .map_or(tag, |(_, token)| token)
+}
+
+/// Return links from the given stackoverflow links.
+///
+///
+/// # Arguments
+///
+/// * `links` - stackoverflow links.
+/// * `restricted_length` - how many links are output at most.
+/// * `splitter` - the separator between links.
+///
+/// # Returns
+/// A list of links with splitter.  Which can directly output by the caller.
+fn answers_links_only(links: &[String], restricted_length: usize, splitter: &str) -> String {
+    let mut results: Vec<String> = Vec::new();
+    // the non-question links are counted in `restricted_length` but skipped,
+    // the same as `get_detailed_answer`.
+    let question_links = links
+        .iter()
+        .take(restricted_length)
+        .filter(|link| is_question_link(link));
+    for link in question_links {
+        let url: Url = match Url::parse(link) {
+            Ok(url) => url,
+            Err(err) => {
+                warn!("Skip invalid link {}, error msg: {:?}", link, err);
+                continue;
+            }
+        };
+        let answer: String = match extract_question(url.path()) {
+            Some(question) => format!("Title - {}\n{}", question, *link,),
+            // short links like `/q/1` have no title, the link is output only.
+            None if question_id(link).is_some() => link.to_string(),
+            None => {
+                warn!("Skip link {} which has no question slug", link);
+                continue;
+            }
+        };
+        results.push(answer);
+    }
+    results.join(splitter)
+}
+
+/// Extract question content, the slug is percent-decoded.
+///
+/// # Examples
+///
+/// let question: Option<String> = extract_question("questions/user_id/the-specific-question");
+/// assert_eq!(question, Some(String::from("the specific question")));
+/// let question: Option<String> = extract_question("questions/user_id/how-to-parse-c%2B%2B?noredirect=1");
+/// assert_eq!(question, Some(String::from("how to parse c++")));
+/// assert_eq!(extract_question("/"), None);
+///
+/// # Returns
+///
+/// The question, or None if the path has no segment, like `/`, or it has no
+/// slug after the question id, like `/questions/123` or `/q/123`.
+fn extract_question(path: &str) -> Option<String> {
+    // The stack overflow question have the following format
+    // https://stackoverflow.com/questions/user_id/the-specific-question
+    // we want to extract the question part out.
+    // the path may ends with `/` or query string, so they are cut off.
+    let path: &str = path.split(['?', '#']).next().unwrap_or_default();
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty()).rev();
+    let slug: &str = segments.next()?;
+    if matches!(segments.next(), Some("questions") | Some("q")) {
+        return None;
+    }
+    // `-` in slug are spaces, while the encoded `%2D` is a real hyphen.
+    let question: String = slug
+        .split('-')
+        .map(|word| percent_decode_str(word).decode_utf8_lossy().into_owned())
+        .collect::<Vec<String>>()
+        .join(" ");
+    Some(question)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{Config, OutputOption};
+    use crate::test_utils::{peak_allocated, MockResponse, MockServer};
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant};
+
+    /// Parse the page to get content of answers only.
+    fn parse_answer(page: String, config: &Config) -> Option<String> {
+        parse_question("", &page, config).answer_text
+    }
+
+    #[test]
+    fn test_parse_question_when_text_contains_entities() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div id="question-header">
+                    <h1><a class="question-hyperlink">What&#39;s the difference of &amp; and &amp;&amp;?</a></h1>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">8</div>
+                    <div class="post-text"><p>&quot;&amp;&amp;&quot; is lazy &lt;3</p><pre><code>if a &amp;&amp; b &gt; 0 { println!(&quot;&amp;lt;&quot;) }</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let conf: Config = Config::new(OutputOption::All, 1, false);
+        let answer: Answer =
+            parse_question("https://stackoverflow.com/questions/1/and", page, &conf);
+        assert_eq!(answer.title, "What's the difference of & and &&?");
+        assert_eq!(
+            answer.answer_text,
+            Some(String::from(
+                r#""&&" is lazy <3
+if a && b > 0 { println!("&lt;") }"#
+            ))
+        );
+
+        // the escaped entity in code is what the author writes, it's kept.
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert_eq!(
+            parse_answer(page.to_string(), &conf),
+            Some(String::from(r#"if a && b > 0 { println!("&lt;") }"#))
+        );
+    }
+
+    #[test]
+    fn test_parse_question_with_multi_part_answer() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div id="question-header">
+                    <h1><a class="question-hyperlink">How to parse json in rust?</a></h1>
+                </div>
+                <a class="post-tag">rust</a>
+                <div class="answer">
+                    <div class="js-vote-count">8</div>
+                    <div class="post-text"><p>Use serde_json:</p><pre><code>serde_json::from_str(data)</code></pre></div>
+                    <div class="post-text"><p>Update: parse into struct:</p><pre><code>let p: Person = serde_json::from_str(data)?;</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let conf: Config = Config::new(OutputOption::All, 1, false);
+        let answer: Answer =
+            parse_question("https://stackoverflow.com/questions/1/json", page, &conf);
+        assert_eq!(
+            answer.answer_text,
+            Some(String::from(
+                "Use serde_json:\nserde_json::from_str(data)\nUpdate: parse into struct:\nlet p: Person = serde_json::from_str(data)?;"
+            ))
+        );
+
+        let conf: Config = Config::new(OutputOption::All, 1, true);
+        let answer_text: String =
+            parse_question("https://stackoverflow.com/questions/1/json", page, &conf)
+                .answer_text
+                .unwrap();
+        let first: usize = answer_text.find("Use serde_json:").unwrap();
+        let update: usize = answer_text.find("Update: parse into struct:").unwrap();
+        assert!(first < update);
+        assert!(answer_text.contains("Person"));
+
+        let conf: Config = Config::new(OutputOption::Markdown, 1, false);
+        assert_eq!(
+            parse_question("https://stackoverflow.com/questions/1/json", page, &conf).answer_text,
+            Some(String::from(
+                "Use serde_json:
+
+```rust
+serde_json::from_str(data)
+```
+
+Update: parse into struct:
+
+```rust
+let p: Person = serde_json::from_str(data)?;
+```"
+            ))
+        );
+
+        // the keyword in the later block is found too.
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_answer_must_contain(Some(String::from("update")));
+        assert!(
+            parse_question("https://stackoverflow.com/questions/1/json", page, &conf)
+                .answer_text
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_parse_question_when_top_answer_has_no_code() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">20</div>
+                    <div class="post-text"><p>Just read the docs.</p></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">10</div>
+                    <div class="post-text"><pre><code>serde_json::from_str(data)</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Answer =
+            parse_question("https://stackoverflow.com/questions/1/json", page, &conf);
+        // the answer without code doesn't take the place of answer with code.
+        assert_eq!(
+            answer.answer_text,
+            Some(String::from("serde_json::from_str(data)"))
+        );
+    }
+
+    #[test]
+    fn test_parse_question_as_json() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div id="question-header">
+                    <h1><a class="question-hyperlink">How to print in rust?</a></h1>
+                </div>
+                <a class="post-tag">rust</a>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text"><p>Use the macro:</p><pre><code>println!("hors")</code></pre></div>
+                    <div class="user-info">
+                        <div class="user-details"><a href="/users/1">author</a></div>
+                        <span class="reputation-score">1,024</span>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        // json output is never colorized.
+        let conf: Config = Config::new(OutputOption::Json, 1, true);
+        let answer: Answer =
+            parse_question("https://stackoverflow.com/questions/1/print", page, &conf);
+        assert_eq!(
+            serde_json::to_value(&answer).unwrap(),
+            serde_json::json!({
+                "link": "https://stackoverflow.com/questions/1/print",
+                "title": "How to print in rust?",
+                "question_tags": ["rust"],
+                "score": 130,
+                "accepted": false,
+                "reputation": 1024,
+                "answer_text": "Use the macro:\nprintln!(\"hors\")"
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_with_question_meta() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div id="question-header">
+                    <h1><a class="question-hyperlink">How to parse json in rust?</a></h1>
+                </div>
+                <time itemprop="dateCreated" datetime="2015-05-18T09:31:02">asked May 18, 2015</time>
+                <a class="post-tag">rust</a>
+                <a class="post-tag">json</a>
+                <div class="answer">
+                    <div class="js-vote-count">42</div>
+                    <div class="post-text"><pre><code>serde_json::from_str(data)</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let links: Vec<String> = vec![server.url("/questions/1/parse-json")];
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_show_question_meta(true);
+
+        let answers: String = get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            answers,
+            format!(
+                "- Answer from {} [tags: rust, json] [asked: 2015-05-18]\nserde_json::from_str(data)",
+                links[0]
+            )
+        );
+
+        // the title line is terse by default.
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Answer = parse_question(&links[0], page, &conf);
+        assert_eq!(
+            answer.to_text(&conf),
+            format!("- Answer from {}\nserde_json::from_str(data)", links[0])
+        );
+    }
+
+    #[test]
+    fn test_parse_question_when_no_title_available() {
+        let conf: Config = Config::new(OutputOption::Json, 1, false);
+        let answer: Answer = parse_question(
+            "https://stackoverflow.com/questions/1/how-to-print",
+            "<html></html>",
+            &conf,
+        );
+        assert_eq!(answer.title, "how to print");
+        assert_eq!(
+            answer.to_text(&conf),
+            "Can't get answer from https://stackoverflow.com/questions/1/how-to-print"
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only() {
+        let links: Vec<String> = vec![String::from(
+            "https://stackoverflow.com/questions/test/how-to-write-function",
+        )];
+        let restricted_length: usize = 1;
+        let results: String = format!(
+            "Title - {}\n{}",
+            "how to write function",
+            "https://stackoverflow.com/questions/test/how-to-write-function"
+        );
+        assert_eq!(
+            answers_links_only(&links, restricted_length, SPLITTER),
+            results
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only_when_contains_links_is_not_question() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/tags/rust"), // this link shouldn't output
+            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
+        ];
+        let restricted_length: usize = 4;
+        let results: String = format!(
+            "Title - {}\n{}",
+            "how to write function",
+            "https://stackoverflow.com/questions/test/how-to-write-function"
+        );
+        assert_eq!(
+            answers_links_only(&links, restricted_length, SPLITTER),
+            results
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only_when_question_and_non_question_links_are_mixed() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/tags/rust"),
+            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
+            String::from("https://stackoverflow.com/users/1/hors"),
+            String::from("https://stackoverflow.com/tags/python"),
+            String::from("https://stackoverflow.com/questions/test/best-practise-for-rust"),
+        ];
+        let results: String = format!(
+            "{}\n{}{}{}\n{}",
+            "Title - how to write function",
+            "https://stackoverflow.com/questions/test/how-to-write-function",
+            SPLITTER,
+            "Title - best practise for rust",
+            "https://stackoverflow.com/questions/test/best-practise-for-rust"
+        );
+        assert_eq!(answers_links_only(&links, 5, SPLITTER), results);
+        // the restricted length still counts the skipped links.
+        assert_eq!(
+            answers_links_only(&links, 4, SPLITTER),
+            "Title - how to write function\nhttps://stackoverflow.com/questions/test/how-to-write-function"
+        );
+        assert_eq!(answers_links_only(&links[..1], 1, SPLITTER), "");
+    }
+
+    #[test]
+    fn test_answer_links_only_when_restricted_size_is_less_than_given_links() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
+            String::from("https://stackoverflow.com/questions/test/best-practise-for-rust"),
+        ];
+        let restricted_length: usize = 1;
+        let results: String = format!(
+            "Title - {}\n{}",
+            "how to write function",
+            "https://stackoverflow.com/questions/test/how-to-write-function"
+        );
+        assert_eq!(
+            answers_links_only(&links, restricted_length, SPLITTER),
+            results
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only_when_restricted_size_is_large_than_given_links() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
+            String::from("https://stackoverflow.com/questions/test/best-practise-for-rust"),
+        ];
+        let restricted_length: usize = 1000;
+        let results: String = format!(
+            "{}\n{}{}{}\n{}",
+            "Title - how to write function",
+            "https://stackoverflow.com/questions/test/how-to-write-function",
+            SPLITTER,
+            "Title - best practise for rust",
+            "https://stackoverflow.com/questions/test/best-practise-for-rust"
+        );
+        assert_eq!(
+            answers_links_only(&links, restricted_length, SPLITTER),
+            results
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_pages_for_each_query() {
+        let server = MockServer::start(vec![MockResponse::new(200, "<html>page</html>")]);
+        let queries_links: Vec<Vec<String>> = vec![
+            vec![
+                server.url("/questions/1/first-query"),
+                server.url("/questions/2/first-query-again"),
+            ],
+            vec![
+                server.url("/about"),
+                server.url("/questions/3/second-query"),
+            ],
+        ];
+        let mut records_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let client: Client = Client::new();
+
+        for links in &queries_links {
+            cache_pages(links, &conf, &mut records_cache, &client)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(server.hits(), 2);
+        let page: Option<&String> = Some(&String::from("<html>page</html>"));
+        assert_eq!(records_cache.get(&queries_links[0][0]), page);
+        assert!(records_cache.get(&queries_links[0][1]).is_none());
+        assert_eq!(records_cache.get(&queries_links[1][1]), page);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_question_page_when_consent_is_required() {
+        let gated_page: &str = r#"
+        <html>
+            <body>
+                <div id="onetrust-consent-sdk">We care about your privacy</div>
+            </body>
+        </html>
+        "#;
+        let page: &str = r#"<html><div class="answer"></div></html>"#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, gated_page).with_header("Set-Cookie", "prov=abc; Path=/"),
+            MockResponse::new(200, page),
+        ]);
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let client: Client = client_builder(&conf).unwrap().build().unwrap();
+
+        let fetched: String = fetch_question_page(&server.url("/questions/1/test"), &client, &conf)
+            .await
+            .unwrap();
+
+        assert_eq!(fetched, page);
+        let requests: Vec<String> = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(!requests[0].to_lowercase().contains("cookie:"));
+        // the cookie set by site is kept together with consent cookie.
+        assert!(requests[1].to_lowercase().contains(&format!(
+            "cookie: prov=abc; {}",
+            CONSENT_COOKIE.to_lowercase()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_question_page_when_consent_is_required_but_not_accepted() {
+        let gated_page: &str = r#"<html><div class="js-consent-banner"></div></html>"#;
+        let server = MockServer::start(vec![MockResponse::new(200, gated_page)]);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_accept_consent(false);
+
+        let fetched: Result<String> =
+            fetch_question_page(&server.url("/questions/1/test"), &Client::new(), &conf).await;
+
+        assert!(fetched.is_err());
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_under_fast_path() {
+        let server = MockServer::start(vec![MockResponse::new(500, "<html>oops</html>")]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/questions/2/test-question"),
+        ];
+        let mut records_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
+        records_cache.put(links[0].clone(), String::from("<html>stale markup</html>"));
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+        conf.set_wayback_fallback(true);
+        conf.set_fast_path(true);
+
+        let answers: Result<String> =
+            get_detailed_answer(&links, &conf, &mut records_cache, &Client::new()).await;
+
+        // the stale page is served, without refetching or going to the second link.
+        assert_eq!(server.hits(), 0);
+        match answers {
+            Err(Error::NoAnswer(no_answer)) => assert_eq!(no_answer, links[..1].to_vec()),
+            other => panic!("NoAnswer error is expected, got {:?}", other),
+        }
+
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+        conf.set_wayback_fallback(true);
+        conf.set_fast_path(true);
+        let fetched: Result<String> = fetch_question_page(&links[1], &Client::new(), &conf).await;
+        // server error is not retried through Wayback Machine.
+        assert_eq!(fetched.unwrap(), "<html>oops</html>");
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_answer_summaries() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div id="question-header"><h1>How to parse json</h1></div>
+                <div class="answer">
+                    <div class="js-vote-count">3</div>
+                    <div class="post-text"><p>Use a streaming parser when the file is huge, so it doesn't need to be loaded into memory at once.</p><pre><code>json.load(f)</code></pre></div>
+                </div>
+                <div class="answer accepted-answer">
+                    <div class="js-vote-count">42</div>
+                    <div class="post-text">
+                        <p>Use json module.</p>
+                        <pre><code>json.loads(data)</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let links: Vec<String> = vec![server.url("/questions/1/parse-json")];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let summaries: Vec<AnswerSummary> = answer_summaries(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            summaries,
+            vec![
+                AnswerSummary {
+                    link: links[0].clone(),
+                    title: String::from("How to parse json"),
+                    score: 42,
+                    accepted: true,
+                    preview: String::from("Use json module."),
+                    body: String::from("json.loads(data)"),
+                },
+                AnswerSummary {
+                    link: links[0].clone(),
+                    title: String::from("How to parse json"),
+                    score: 3,
+                    accepted: false,
+                    preview: String::from(
+                        "Use a streaming parser when the file is huge, so it doesn't need to be loaded i…"
+                    ),
+                    body: String::from("json.load(f)"),
+                },
+            ]
+        );
+        // the page is fetched only once for all answers.
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_no_page_gives_answer() {
+        let server = MockServer::start(vec![MockResponse::new(200, "<html>no answers</html>")]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/tags/json"),
+            server.url("/questions/2/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 3, false);
+
+        let answers: Result<String> = get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await;
+
+        match answers {
+            Err(Error::NoAnswer(no_answer)) => {
+                assert_eq!(no_answer, vec![links[0].clone(), links[2].clone()])
+            }
+            other => panic!("NoAnswer error is expected, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_question_has_no_answer() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">7</div>
+                    <div class="post-text"><pre><code>good answer</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "<html>no answers</html>"),
+            MockResponse::new(200, page),
+        ]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/questions/2/test-question"),
+            server.url("/questions/3/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let answers: String = get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        // the question without answer doesn't count, and the budget is met by
+        // the second link, so the third one isn't fetched.
+        assert_eq!(answers, format!("- Answer from {}\ngood answer", links[1]));
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_later_page_cant_be_fetched() {
+        let page: &str = r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text"><pre><code>good answer</code></pre></div></div>"#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, page),
+            MockResponse::new(200, "<html>no answers</html>"),
+        ]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/questions/2/test-question"),
+            String::from("http://127.0.0.1:1/questions/3/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+
+        let answers: String = get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        // the answer which is already got isn't thrown away.
+        assert_eq!(answers, format!("- Answer from {}\ngood answer", links[0]));
+
+        // the error is returned when there is no answer to give.
+        let answers: Result<String> = get_detailed_answer(
+            &links[2..],
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await;
+        assert!(answers.is_err());
+        assert!(!matches!(answers, Err(Error::NoAnswer(_))));
+    }
+
+    #[tokio::test]
+    async fn test_api_answers_from() {
+        let server = MockServer::start(vec![
+            MockResponse::new(
+                200,
+                r#"{"items": [
+                    {"answer_id": 10, "question_id": 1, "score": 3, "is_accepted": false, "body": "<pre><code>voted answer</code></pre>"},
+                    {"answer_id": 11, "question_id": 1, "score": 2, "is_accepted": true, "body": "<pre><code>accepted answer</code></pre>"}
+                ]}"#,
+            ),
+            MockResponse::new(
+                200,
+                r#"{"items": [
+                    {"answer_id": 20, "question_id": 5, "score": 7, "is_accepted": false, "body": "<pre><code>unix answer</code></pre>"}
+                ]}"#,
+            ),
+        ]);
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/test-question"),
+            String::from("https://stackoverflow.com/questions/2/no-answer"),
+            String::from("https://unix.stackexchange.com/questions/5/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 3, false);
+
+        let answers: String = api_answers_from(&links, &conf, &Client::new(), &server.url(""))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            answers,
+            format!(
+                "- Answer from {}\nvoted answer{}- Answer from {}\nunix answer",
+                links[0],
+                conf.splitter(),
+                links[2]
+            )
+        );
+        // one request for each site.
+        let requests: Vec<String> = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("/questions/1;2/answers?"));
+        assert!(requests[0].contains("&site=stackoverflow&"));
+        assert!(requests[1].contains("/questions/5/answers?"));
+        assert!(requests[1].contains("&site=unix&"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_answers_when_question_has_no_answer() {
+        let page: &str = r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text"><pre><code>good answer</code></pre></div></div>"#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "<html>no answers</html>"),
+            MockResponse::new(200, page),
+        ]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/questions/2/test-question"),
+            server.url("/questions/3/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let mut answers: Vec<String> = vec![];
+        stream_answers(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+            |answer| answers.push(answer),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            answers,
+            vec![format!("- Answer from {}\ngood answer", links[1])]
+        );
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_show_related() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <pre><code>println!("answer")</code></pre>
+                    </div>
+                </div>
+                <div id="sidebar">
+                    <div class="module sidebar-linked">
+                        <div class="linked">
+                            <div class="spacer">
+                                <a href="/q/10" class="question-hyperlink">Linked question</a>
+                            </div>
+                        </div>
+                    </div>
+                    <div class="module sidebar-related">
+                        <div class="related js-gps-related-questions">
+                            <div class="spacer">
+                                <a href="/questions/11/related" class="question-hyperlink">Related question</a>
+                            </div>
+                            <div class="spacer">
+                                <a href="/q/10" class="question-hyperlink">Linked question</a>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/questions/2/test-question"),
+        ];
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+        conf.set_show_related(true);
+
+        let answers: String = get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        let related: String = format!(
+            "Related questions:
+- Linked question
+  {}
+- Related question
+  {}",
+            server.url("/q/10"),
+            server.url("/questions/11/related")
+        );
+        // only the top question lists related questions.
+        assert_eq!(answers.matches(&related).count(), 1);
+        assert!(answers.starts_with(&format!(
+            "- Answer from {}\nprintln!(\"answer\")\n{}",
+            links[0], related
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_numbers_and_answers_per_question_are_set() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">30</div>
+                    <div class="post-text"><pre><code>first</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">20</div>
+                    <div class="post-text"><pre><code>second</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">10</div>
+                    <div class="post-text"><pre><code>third</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let get = |numbers: u8, answers_per_question: u8| async move {
+            let server = MockServer::start(vec![MockResponse::new(200, page)]);
+            let links: Vec<String> = (1..=3)
+                .map(|id| server.url(&format!("/questions/{}/test-question", id)))
+                .collect();
+            let mut conf: Config = Config::new(OutputOption::OnlyCode, numbers, false);
+            conf.set_answers_per_question(answers_per_question);
+            let answers: String = get_detailed_answer(
+                &links,
+                &conf,
+                &mut AnswerRecordsCache::load_empty(),
+                &Client::new(),
+            )
+            .await
+            .unwrap();
+            (answers, server.hits())
+        };
+
+        // one question page, and three answers from it.
+        let (answers, hits) = get(1, 3).await;
+        assert_eq!(hits, 1);
+        assert_eq!(answers.matches("- Answer from").count(), 1);
+        assert!(answers.ends_with(&format!(
+            "first{}second{}third",
+            ANSWER_SPLITTER, ANSWER_SPLITTER
+        )));
+
+        // three question pages, and the top answer from each of them.
+        let (answers, hits) = get(3, 1).await;
+        assert_eq!(hits, 3);
+        assert_eq!(answers.matches("- Answer from").count(), 3);
+        assert_eq!(answers.matches("first").count(), 3);
+        assert!(!answers.contains("second"));
+    }
+
+    #[test]
+    fn test_format_pages_ranks_answers_of_several_sites() {
+        let answer = |link: &str, score: Option<i16>| Answer {
+            link: link.to_string(),
+            title: String::from("test question"),
+            question_tags: vec![],
+            asked: None,
+            score,
+            accepted: false,
+            reputation: None,
+            answer_text: Some(String::from("answer")),
+        };
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/a"),
+            String::from("https://serverfault.com/questions/2/b"),
+            String::from("https://stackoverflow.com/questions/3/c"),
+        ];
+        let pages = || {
+            vec![
+                (String::new(), answer(&links[0], Some(3))),
+                (String::new(), answer(&links[1], Some(10))),
+                (String::new(), answer(&links[2], None)),
+            ]
+        };
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 3, false);
+        let answer_links = |answers: String| -> Vec<String> {
+            answers
+                .lines()
+                .filter_map(|line| line.strip_prefix("- Answer from "))
+                .map(String::from)
+                .collect()
+        };
+
+        // answers of one site are kept in the order of search result.
+        let answers: String = format_pages(pages(), &links, &conf).unwrap();
+        assert_eq!(answer_links(answers), links);
+
+        conf.set_sites(vec![
+            String::from("stackoverflow.com"),
+            String::from("serverfault.com"),
+        ]);
+        let answers: String = format_pages(pages(), &links, &conf).unwrap();
+        assert_eq!(
+            answer_links(answers),
+            vec![links[1].clone(), links[0].clone(), links[2].clone()]
+        );
+    }
+
+    #[test]
+    fn test_format_answers_with_custom_splitter() {
+        let answer = |link: &str, text: &str| Answer {
+            link: link.to_string(),
+            title: String::from("test question"),
+            question_tags: vec![],
+            asked: None,
+            score: None,
+            accepted: false,
+            reputation: None,
+            answer_text: Some(text.to_string()),
+        };
+        let pages: Vec<(String, Answer)> = vec![
+            (
+                String::new(),
+                answer("https://stackoverflow.com/questions/1/a", "first"),
+            ),
+            (
+                String::new(),
+                answer("https://stackoverflow.com/questions/2/b", "second"),
+            ),
+        ];
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+        conf.set_splitter("\n---\n");
+        assert_eq!(
+            format_answers(pages, &conf),
+            "- Answer from https://stackoverflow.com/questions/1/a\nfirst\n---\n\
+             - Answer from https://stackoverflow.com/questions/2/b\nsecond"
+        );
+
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/parse-json"),
+            String::from("https://stackoverflow.com/questions/2/parse-yaml"),
+        ];
+        assert_eq!(
+            answers_links_only(&links, 2, conf.splitter()),
+            "Title - parse json\nhttps://stackoverflow.com/questions/1/parse-json\n---\n\
+             Title - parse yaml\nhttps://stackoverflow.com/questions/2/parse-yaml"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_fetches_pages_concurrently() {
+        let page = |code: &str| {
+            format!(
+                r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text"><pre><code>{}</code></pre></div></div>"#,
+                code
+            )
+        };
+        // the first page is the slowest, but it's still output first.
+        let slow = MockServer::start(vec![
+            MockResponse::new(200, &page("first")).with_delay(Duration::from_millis(600))
+        ]);
+        let fast = MockServer::start(vec![
+            MockResponse::new(200, &page("second")).with_delay(Duration::from_millis(300))
+        ]);
+        let links: Vec<String> = vec![
+            slow.url("/questions/1/test-question"),
+            String::from("https://stackoverflow.com/tags/rust"),
+            fast.url("/questions/2/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 3, false);
+
+        let start: Instant = Instant::now();
+        let answers: String = get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(start.elapsed() < Duration::from_millis(900));
+        assert_eq!(
+            answers,
+            format!(
+                "- Answer from {}\nfirst{}- Answer from {}\nsecond",
+                links[0], SPLITTER, links[2]
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_answers_gives_each_answer_as_soon_as_it_is_parsed() {
+        let page = |code: &str| {
+            format!(
+                r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text"><pre><code>{}</code></pre></div></div>"#,
+                code
+            )
+        };
+        let fast = MockServer::start(vec![MockResponse::new(200, &page("first"))]);
+        let slow = MockServer::start(vec![
+            MockResponse::new(200, &page("second")).with_delay(Duration::from_millis(600))
+        ]);
+        let links: Vec<String> = vec![
+            fast.url("/questions/1/test-question"),
+            slow.url("/questions/2/test-question"),
+        ];
+        let conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+
+        let start: Instant = Instant::now();
+        let mut answers: Vec<(String, Duration)> = vec![];
+        stream_answers(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+            |answer| answers.push((answer, start.elapsed())),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0].0, format!("- Answer from {}\nfirst", links[0]));
+        assert_eq!(answers[1].0, format!("- Answer from {}\nsecond", links[1]));
+        // the first answer doesn't wait for the slow page.
+        assert!(answers[0].1 < Duration::from_millis(400));
+        assert!(answers[1].1 >= Duration::from_millis(600));
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_cached_page_is_stale() {
+        let fresh_page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <pre><code>println!("fresh")</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, fresh_page)]);
+        let link: String = server.url("/questions/1/test-question");
+        let mut records_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
+        records_cache.put(link.clone(), String::from("<html>stale markup</html>"));
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let links: Vec<String> = vec![link.clone()];
+
+        let answers: String =
+            get_detailed_answer(&links, &conf, &mut records_cache, &Client::new())
+                .await
+                .unwrap();
+
+        assert_eq!(server.hits(), 1);
+        assert!(answers.contains(r#"println!("fresh")"#));
+        assert_eq!(records_cache.get(&link), Some(&String::from(fresh_page)));
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_stale_page_cant_be_fetched() {
+        let page: &str = r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text"><pre><code>good answer</code></pre></div></div>"#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let stale_link: String = String::from("http://127.0.0.1:1/questions/1/test-question");
+        let links: Vec<String> = vec![stale_link.clone(), server.url("/questions/2/test-question")];
+        let mut records_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
+        records_cache.put(
+            stale_link.clone(),
+            String::from("<html>stale markup</html>"),
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let answers: String =
+            get_detailed_answer(&links, &conf, &mut records_cache, &Client::new())
+                .await
+                .unwrap();
+
+        // the failure doesn't abort the run, and the cached page is kept.
+        assert_eq!(answers, format!("- Answer from {}\ngood answer", links[1]));
+        assert_eq!(
+            records_cache.get(&stale_link),
+            Some(&String::from("<html>stale markup</html>"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_detailed_answer_when_save_html_dir_is_set() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <pre><code>println!("saved")</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let links: Vec<String> = vec![server.url("/questions/4242/test-question")];
+        let dir: PathBuf = std::env::temp_dir().join(format!("hors-html-{}", std::process::id()));
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_save_html_dir(Some(dir.clone()));
+
+        get_detailed_answer(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        let saved: String = fs::read_to_string(dir.join("4242.html")).unwrap();
+        assert_eq!(saved, page);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_answers_from_file() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <pre><code>println!("offline")</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let link: String = server.url("/questions/1/test-question");
+        let path: PathBuf =
+            std::env::temp_dir().join(format!("hors-offline-{}.html", std::process::id()));
+        fs::write(&path, page).unwrap();
+        let conf = || {
+            let mut conf: Config = Config::new(OutputOption::All, 1, true);
+            conf.set_explain_ranking(true);
+            conf
+        };
+
+        let from_network: String = get_detailed_answer(
+            std::slice::from_ref(&link),
+            &conf(),
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+        let from_file: String = get_answers_from_file(&path, &link, conf()).unwrap();
+
+        assert!(from_file.contains("offline"));
+        assert_eq!(from_file, from_network);
+        fs::remove_file(&path).unwrap();
+        assert!(get_answers_from_file(&path, &link, conf()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_answers_from_html() {
+        let page: &str = r#"
+        <html>
+            <head><link rel="canonical" href="https://stackoverflow.com/questions/1/test-question"></head>
+            <body>
+                <a class="post-tag">rust</a>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <p>Print it:</p>
+                        <pre><code>println!("parsed")</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let link: String = server.url("/questions/1/test-question");
+        let conf: Config = Config::new(OutputOption::All, 1, true);
+
+        let from_network: String = get_detailed_answer(
+            std::slice::from_ref(&link),
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+        let from_html: String = parse_answers_from_html(page, &conf).unwrap();
+
+        // the page from network is answered under the link it's fetched from.
+        assert!(from_html
+            .starts_with("- Answer from https://stackoverflow.com/questions/1/test-question"));
+        assert_eq!(
+            from_html,
+            from_network.replace(&link, "https://stackoverflow.com/questions/1/test-question")
+        );
+        assert!(matches!(
+            parse_answers_from_html("<html></html>", &conf),
+            Err(Error::NoAnswer(_))
+        ));
+
+        // the output is post-processed like the answers from network.
+        if cfg!(unix) {
+            let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+            conf.set_formatter_cmd(Some(String::from("tr a-z A-Z")));
+            let page: String = page.replace("rel=\"canonical\"", "rel=\"alternate\"");
+            assert_eq!(
+                parse_answers_from_html(&page, &conf).unwrap(),
+                "PRINTLN!(\"PARSED\")"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stream_answers_with_formatter() {
+        let page: &str = r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text"><pre><code>first</code></pre></div></div>"#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let links: Vec<String> = vec![
+            server.url("/questions/1/test-question"),
+            server.url("/questions/2/test-question"),
+        ];
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+        conf.set_show_header(false);
+        conf.set_formatter_cmd(Some(String::from("tr a-z A-Z")));
+
+        let mut answers: Vec<String> = vec![];
+        stream_answers(
+            &links,
+            &conf,
+            &mut AnswerRecordsCache::load_empty(),
+            &Client::new(),
+            |answer| answers.push(answer),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(answers, vec!["FIRST", "FIRST"]);
+    }
+
+    #[test]
+    fn test_postprocessor_when_output_is_json() {
+        let lines: String = (1..=500)
+            .map(|line| format!("\"line {}\"", line))
+            .collect::<Vec<String>>()
+            .join(",\n");
+        let output: String = format!("[\n{}\n]", lines);
+        let mut conf: Config = Config::new(OutputOption::Json, 1, false);
+        conf.set_fit_to_terminal(true);
+        conf.set_formatter_cmd(Some(String::from("tr a-z A-Z")));
+
+        assert_eq!(postprocessor(&conf)(output.clone()), output);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_with_wayback_when_live_page_is_unavailable() {
+        let archived_page: &str = "<html>archived</html>";
+        let server = MockServer::start(vec![MockResponse::new(200, archived_page)]);
+        let wayback_prefix: String = server.url("/web/2id_/");
+
+        let page: String = fetch_page_with_wayback(
+            "http://127.0.0.1:1/questions/1/test-question",
+            &Client::new(),
+            &Config::new(OutputOption::OnlyCode, 1, false),
+            &wayback_prefix,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(page, archived_page);
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_with_wayback_when_live_page_is_available() {
+        let server = MockServer::start(vec![MockResponse::new(200, "<html>live</html>")]);
+
+        let page: String = fetch_page_with_wayback(
+            &server.url("/questions/1/test-question"),
+            &Client::new(),
+            &Config::new(OutputOption::OnlyCode, 1, false),
+            "http://127.0.0.1:1/web/2id_/",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(page, "<html>live</html>");
+    }
+
+    #[test]
+    fn test_parse_answer() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <pre>
+                            <code>println!("hello world")</code>
+                        </pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Option<String> = parse_answer(page, &conf);
+
+        assert!(answer.is_some());
+
+        if let Some(code) = answer {
+            assert_eq!(code.trim(), String::from(r#"println!("hello world")"#));
+        }
+    }
+
+    #[test]
+    fn test_parse_answer_when_pre_and_code_both_existed() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <p>answer <code>goto</code> here </p>
+                        <pre>
+                            <code>println!("hello world")</code>
+                        </pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Option<String> = parse_answer(page, &conf);
+
+        assert!(answer.is_some());
+
+        if let Some(code) = answer {
+            assert_eq!(code.trim(), String::from(r#"println!("hello world")"#));
+        }
+    }
+
+    #[test]
+    fn test_parse_answer_when_no_code_available() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <p>answer here </p>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Option<String> = parse_answer(page, &conf);
+
+        assert!(answer.is_none());
+    }
+
+    #[test]
+    fn test_parse_answer_when_no_code_available_and_prose_fallback_enabled() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <p>Prefer composition over inheritance.</p>
+                        <p>Second paragraph here.</p>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_code_mode_prose_fallback(true);
+        let answer: Option<String> = parse_answer(page, &conf);
+
+        assert_eq!(
+            answer,
+            Some(String::from("Prefer composition over inheritance."))
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_when_only_code_existed() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <p>answer <code>goto</code> here </p>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Option<String> = parse_answer(page, &conf);
+
+        assert!(answer.is_some());
+
+        if let Some(code) = answer {
+            assert_eq!(code.trim(), String::from("goto"));
+        }
+    }
+
+    #[test]
+    fn test_parse_answer_with_line_numbers() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <pre><code>a = 1
+b = 2
+c = 3
+d = 4
+e = 5
+f = 6
+g = 7
+h = 8
+i = 9
+j = 10</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_code_line_numbers(true);
+        let answer: String = parse_answer(page, &conf).unwrap();
+        let lines: Vec<&str> = answer.lines().collect();
+
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], " 1 | a = 1");
+        assert_eq!(lines[8], " 9 | i = 9");
+        assert_eq!(lines[9], "10 | j = 10");
+    }
+
+    #[test]
+    fn test_colorized_code_with_theme() {
+        let code: String = String::from("x = 1\n");
+        let tags: Vec<String> = vec![String::from("python")];
+        let colorize = |conf: &Config| {
+            colorized_code(
+                code.clone(),
+                &tags,
+                &SYNTAX_SET,
+                None,
+                theme(conf),
+                ColorDepth::TrueColor,
+            )
+        };
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, true);
+        let default: String = colorize(&conf);
+        conf.set_theme(Some(String::from(DEFAULT_THEME))).unwrap();
+        assert_eq!(colorize(&conf), default);
+        conf.set_theme(Some(String::from("InspiredGitHub")))
+            .unwrap();
+        assert_ne!(colorize(&conf), default);
+    }
+
+    #[test]
+    fn test_colorized_code_with_theme_file() {
+        let path: PathBuf =
+            std::env::temp_dir().join(format!("hors-red-{}.tmTheme", std::process::id()));
+        fs::write(
+            &path,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>name</key>
+    <string>Hors Red</string>
+    <key>settings</key>
+    <array>
+        <dict>
+            <key>settings</key>
+            <dict>
+                <key>background</key>
+                <string>#000000</string>
+                <key>foreground</key>
+                <string>#FF0000</string>
+            </dict>
+        </dict>
+    </array>
+</dict>
+</plist>"#,
+        )
+        .unwrap();
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, true);
+        conf.set_theme(Some(String::from("InspiredGitHub")))
+            .unwrap();
+        conf.set_theme_file(&path).unwrap();
+
+        let colorized: String = colorized_code(
+            String::from("x = 1\n"),
+            &[String::from("python")],
+            &SYNTAX_SET,
+            None,
+            theme(&conf),
+            ColorDepth::TrueColor,
+        );
+
+        // the theme file wins, so everything is red.
+        assert_eq!(
+            colorized.matches("\x1b[38;2;").count(),
+            colorized.matches("\x1b[38;2;255;0;0m").count()
+        );
+        assert!(colorized.contains("\x1b[38;2;255;0;0mx"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_colorized_code_with_language() {
+        let code: String = String::from("fn main() {}\n");
+        let tags: Vec<String> = vec![String::from("linux")];
+        let guessed: String = colorized_code(
+            code.clone(),
+            &tags,
+            &SYNTAX_SET,
+            None,
+            &THEME_SET.themes[DEFAULT_THEME],
+            ColorDepth::TrueColor,
+        );
+        let rust: String = colorized_code(
+            code.clone(),
+            &tags,
+            &SYNTAX_SET,
+            Some("rust"),
+            &THEME_SET.themes[DEFAULT_THEME],
+            ColorDepth::TrueColor,
+        );
+        assert_ne!(rust, guessed);
+        assert_eq!(
+            rust,
+            colorized_code(
+                code.clone(),
+                &[String::from("rust")],
+                &SYNTAX_SET,
+                None,
+                &THEME_SET.themes[DEFAULT_THEME],
+                ColorDepth::TrueColor
+            )
+        );
+        // unknown language falls back to plain text, rather than guessing by tags.
+        assert_eq!(
+            colorized_code(
+                code.clone(),
+                &[String::from("rust")],
+                &SYNTAX_SET,
+                Some("not-a-language"),
+                &THEME_SET.themes[DEFAULT_THEME],
+                ColorDepth::TrueColor
+            ),
+            guessed
+        );
+        // the given language is resolved through aliases like tags.
+        assert_eq!(
+            colorized_code(
+                code.clone(),
+                &tags,
+                &SYNTAX_SET,
+                Some("golang"),
+                &THEME_SET.themes[DEFAULT_THEME],
+                ColorDepth::TrueColor
+            ),
+            colorized_code(
+                code,
+                &[String::from("go")],
+                &SYNTAX_SET,
+                None,
+                &THEME_SET.themes[DEFAULT_THEME],
+                ColorDepth::TrueColor
+            )
+        );
+    }
+
+    #[test]
+    fn test_guess_syntax_by_alias() {
+        let ss = SyntaxSet::load_defaults_newlines();
+        let guess = |tags: &[&str]| {
+            let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+            guess_syntax(&tags, "", &ss).name.clone()
+        };
+        assert_eq!(guess(&["js"]), "JavaScript");
+        assert_eq!(guess(&["golang"]), "Go");
+        assert_eq!(guess(&["c#"]), "C#");
+        assert_eq!(guess(&["c++"]), "C++");
+        assert_eq!(guess(&["python-3.x"]), "Python");
+        // the first tag which resolves wins.
+        assert_eq!(guess(&["linux", "golang", "python"]), "Go");
+        assert_eq!(guess(&["linux"]), "Plain Text");
+    }
+
+    #[test]
+    fn test_guess_syntax_when_tags_are_missing() {
+        let ss = SyntaxSet::load_defaults_newlines();
+        let guess = |code: &str| guess_syntax(&[], code, &ss).name.clone();
+        assert_eq!(guess("#!/usr/bin/env python\nprint('hors')\n"), "Python");
+        assert_eq!(
+            guess("import json\n\ndef load(path):\n    return json.load(open(path))\n"),
+            "Python"
+        );
+        assert_eq!(
+            guess("fn main() {\n    let v: Vec<u8> = Vec::new();\n}\n"),
+            "Rust"
+        );
+        assert_eq!(
+            guess("#!/bin/bash\necho hors\n"),
+            "Bourne Again Shell (bash)"
+        );
+        assert_eq!(
+            guess("$ cargo install hors\n$ hors --version | head -1\n"),
+            "Bourne Again Shell (bash)"
+        );
+        assert_eq!(guess("hello world"), "Plain Text");
+        // tags are trusted before the content.
+        let tags: Vec<String> = vec![String::from("ruby")];
+        assert_eq!(guess_syntax(&tags, "def hors\nend\n", &ss).name, "Ruby");
+    }
+
+    #[test]
+    fn test_guess_syntax_with_custom_syntaxes() {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("hors-custom-syntax-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("HorsDSL.sublime-syntax"),
+            "name: HorsDSL\nfile_extensions: [horsdsl]\nscope: source.horsdsl\ncontexts:\n  main: []\n",
+        )
+        .unwrap();
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, true);
+        let tags: Vec<String> = vec![String::from("horsdsl")];
+        assert_eq!(
+            guess_syntax(&tags, "", syntax_set(&conf)).name,
+            "Plain Text"
+        );
+
+        conf.set_syntax_dir(&dir, false).unwrap();
+        assert_eq!(guess_syntax(&tags, "", syntax_set(&conf)).name, "HorsDSL");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_answer_colorized_with_many_code_blocks() {
+        let block: &str = "<pre><code>fn main() {\n    println!(\"hors\");\n}</code></pre>";
+        let page: String = format!(
+            r#"<div class="answer"><div class="js-vote-count">1</div><div class="post-text">{}</div></div>"#,
+            block.repeat(20)
+        );
+        let conf: Config = Config::new(OutputOption::All, 1, true);
+
+        let answer: Option<String> = parse_answer(page, &conf);
+
+        assert_eq!(answer.unwrap().matches("hors").count(), 20);
+        // every block is colorized with the syntaxes and theme loaded once.
+        assert!(std::ptr::eq(syntax_set(&conf), &*SYNTAX_SET));
+        assert!(std::ptr::eq(syntax_set(&conf), syntax_set(&conf)));
+        assert!(std::ptr::eq(theme(&conf), &THEME_SET.themes[DEFAULT_THEME]));
+    }
+
+    #[test]
+    fn test_colorized_code_with_256_colors() {
+        let code: String = String::from("fn main() {}\n");
+        let tags: Vec<String> = vec![String::from("rust")];
+        let colorized: String = colorized_code(
+            code,
+            &tags,
+            &SYNTAX_SET,
+            None,
+            &THEME_SET.themes[DEFAULT_THEME],
+            ColorDepth::Ansi256,
+        );
+        assert!(colorized.contains("\x1b[38;5;"));
+        assert!(!colorized.contains("\x1b[38;2;"));
+        assert!(colorized.contains("main"));
+    }
+
+    #[test]
+    fn test_number_lines_when_code_is_colorized() {
+        let colorized: String = colorized_code(
+            String::from("x = 1\ny = 2\n"),
+            &[],
+            &SYNTAX_SET,
+            None,
+            &THEME_SET.themes[DEFAULT_THEME],
+            ColorDepth::TrueColor,
+        );
+        let numbered: String = number_lines(&colorized, true);
+        let lines: Vec<&str> = numbered.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        // the color of previous line doesn't leak into the line number.
+        assert!(lines[0].starts_with("\x1b[0m1 | \x1b["));
+        assert!(lines[1].starts_with("\x1b[0m2 | \x1b["));
+        assert!(lines[1].ends_with("\x1b[0m"));
+        assert!(numbered.ends_with("\x1b[0m\n"));
+        assert_eq!(number_lines("x = 1\n", false), "1 | x = 1\n");
+    }
+
+    #[test]
+    fn test_parse_answer_when_answer_only_contains_image() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="s-prose js-post-body">
+                        <p><a href="https://i.sstatic.net/abc.png"><img src="https://i.sstatic.net/abc.png" alt="enter image description here"></a></p>
+                    </div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">20</div>
+                    <div class="s-prose js-post-body">
+                        <pre><code>println!("text answer")</code></pre>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::All, 1, false);
+        let answer: Option<String> = parse_answer(page.clone(), &conf);
+        assert_eq!(
+            answer,
+            Some(String::from(
+                "This answer is a screenshot: https://i.sstatic.net/abc.png"
+            ))
+        );
+
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let answer: Option<String> = parse_answer(page, &conf);
+        assert_eq!(answer, Some(String::from(r#"println!("text answer")"#)));
+    }
+
+    #[test]
+    fn test_parse_answer_when_sanitize_control_chars_enabled() {
+        let page: String = String::from(
+            "
+        <html>
+            <body>
+                <div class=\"answer\">
+                    <div class=\"js-vote-count\">130</div>
+                    <div class=\"post-text\">
+                        <p>run\x1b[2J it\tnow\x07</p>
+                    </div>
+                </div>
+            </body>
+        </html>
+        ",
+        );
+        let conf: Config = Config::new(OutputOption::All, 1, false);
+        let answer: String = parse_answer(page.clone(), &conf).unwrap();
+        assert!(answer.contains('\x1b'));
+
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_sanitize_control_chars(true);
+        let answer: String = parse_answer(page, &conf).unwrap();
+        assert_eq!(answer.trim(), "run\\u{1b}[2J it\tnow\\u{7}");
+    }
+
+    #[test]
+    fn test_parse_answer_when_answer_must_contain_keyword() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">9000</div>
+                    <div class="post-text">
+                        <p>use <code>serde_json</code> here</p>
+                    </div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text">
+                        <p>use <code>simd-json</code> here</p>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_answer_must_contain(Some(String::from("SIMD")));
+        let answer: Option<String> = parse_answer(page.clone(), &conf);
+        assert_eq!(answer.unwrap().trim(), "use simd-json here");
+
+        conf.set_answer_must_contain(Some(String::from("not existed")));
+        let answer: Option<String> = parse_answer(page, &conf);
+        assert!(answer.is_none());
+    }
+
+    #[test]
+    fn test_parse_answer_when_recency_is_weighted() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">900</div>
+                    <div class="post-text"><p>old answer</p></div>
+                    <span title="2009-03-01 10:00:00Z" class="relativetime">Mar 1 '09</span>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">300</div>
+                    <div class="post-text"><p>new answer</p></div>
+                    <time datetime="2020-06-01T10:00:00">Jun 1 '20</time>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        let answer: Option<String> = parse_answer(page.clone(), &conf);
+        assert_eq!(answer.unwrap().trim(), "old answer");
+
+        conf.set_recency_weight(1.0);
+        let answer: Option<String> = parse_answer(page, &conf);
+        assert_eq!(answer.unwrap().trim(), "new answer");
+    }
+
+    #[test]
+    fn test_rank_answers_by_language_then_votes() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">900</div>
+                    <div class="post-text"><pre class="lang-py"><code>print(1)</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">30</div>
+                    <div class="post-text"><pre class="lang-rust"><code>println!("1")</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">500</div>
+                    <div class="post-text"><pre><code class="hljs language-rust">dbg!(1)</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">100</div>
+                    <div class="post-text"><pre><code>echo 1</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let doc: Document = Document::from(page.as_str());
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_language(Some(String::from("Rust")));
+        let votes: Vec<i16> = rank_answers(&doc, &conf)
+            .iter()
+            .map(|candidate| candidate.votes)
+            .collect();
+        assert_eq!(votes, vec![900, 500, 100, 30]);
+
+        conf.set_rank(Rank::LanguageThenVotes);
+        let votes: Vec<i16> = rank_answers(&doc, &conf)
+            .iter()
+            .map(|candidate| candidate.votes)
+            .collect();
+        assert_eq!(votes, vec![500, 30, 900, 100]);
+    }
+
+    #[test]
+    fn test_explain_ranking() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer" data-answerid="11">
+                    <div class="js-vote-count">-1</div>
+                    <div class="post-text"><pre><code>echo -1</code></pre></div>
+                </div>
+                <div class="answer accepted-answer" data-answerid="12">
+                    <div class="js-vote-count">10</div>
+                    <div class="post-text"><pre><code>echo 10</code></pre></div>
+                </div>
+                <div class="answer" data-answerid="13">
+                    <div class="js-vote-count">40</div>
+                    <div class="post-text"><pre><code>echo 40</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert_eq!(
+            explain_ranking(&page, &conf),
+            "Ranking of answers:
+  answer #1 (id 11): skipped, because its score is below the minimum
+  answer #2 (id 12): not chosen, ranked #2, score 0.25 (votes: 10, accepted: true, recency: 0.00, language match: false)
+  answer #3 (id 13): chosen, score 1.00 (votes: 40, accepted: false, recency: 0.00, language match: false)"
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_when_show_reputation() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">10</div>
+                    <div class="post-text"><pre><code>echo 1</code></pre></div>
+                    <div class="post-signature">
+                        <div class="user-info">
+                            <div class="user-details"><a href="/users/1">editor</a></div>
+                            <span class="reputation-score">1,024</span>
+                        </div>
+                    </div>
+                    <div class="post-signature owner">
+                        <div class="user-info">
+                            <div class="user-details"><a href="/users/2">author</a></div>
+                            <span class="reputation-score" title="reputation score 12,345">12.3k</span>
+                        </div>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from("echo 1"))
+        );
+
+        conf.set_show_reputation(true);
+        assert_eq!(
+            parse_answer(page, &conf),
+            Some(String::from(
+                "Answered by author (reputation 12300)\necho 1"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_as_markdown() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <a class="post-tag">golang</a>
+                <div class="answer">
+                    <div class="js-vote-count">7</div>
+                    <div class="post-text">
+                        <p>Use <a href="https://golang.org/pkg/strings">strings</a>:</p>
+                        <pre><code>strings.Split(s, ",")</code></pre>
+                        <ul><li>it returns a slice</li></ul>
+                    </div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::Markdown, 1, true);
+        assert_eq!(
+            parse_answer(page, &conf),
+            Some(String::from(
+                "Use [strings](https://golang.org/pkg/strings):\n\n```go\nstrings.Split(s, \",\")\n```\n\n- it returns a slice"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_when_edited_after_is_set() {
+        let answer = |votes: u8, code: &str, answered: &str, edited: Option<&str>| {
+            let edited: String = edited
+                .map(|time| {
+                    format!(
+                        r#"<div class="user-action-time">edited <span title="{}" class="relativetime"></span></div>"#,
+                        time
+                    )
+                })
+                .unwrap_or_default();
+            format!(
+                r#"<div class="answer">
+                    <div class="js-vote-count">{}</div>
+                    <div class="post-text"><pre><code>{}</code></pre></div>
+                    {}
+                    <div class="user-action-time">answered <span title="{}" class="relativetime"></span></div>
+                </div>"#,
+                votes, code, edited, answered
+            )
+        };
+        let page: String = format!(
+            "<html><body>{}{}{}</body></html>",
+            answer(90, "old", "2009-05-01 10:00:00Z", None),
+            answer(
+                30,
+                "edited",
+                "2010-05-01 10:00:00Z",
+                Some("2019-03-01 10:00:00Z")
+            ),
+            answer(10, "new", "2020-01-01 10:00:00Z", None),
+        );
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_answers_per_question(3);
+        conf.set_edited_after(Some(2018));
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(format!("edited{}new", ANSWER_SPLITTER))
+        );
+
+        // every answer predates the year, so the best one is chosen anyway.
+        conf.set_answers_per_question(1);
+        conf.set_edited_after(Some(2021));
+        assert_eq!(parse_answer(page.clone(), &conf), Some(String::from("old")));
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_edited_after(Some(2021));
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from(
+                "Note: no answer is edited in or after 2021, showing the best one.\nold"
+            ))
+        );
+
+        conf.set_edited_after(Some(2018));
+        assert_eq!(
+            explain_ranking(&page, &conf),
+            "Ranking of answers:
+  answer #1: skipped, because it's edited before 2018
+  answer #2: chosen, score 0.33 (votes: 30, accepted: false, recency: 0.92, language match: false)
+  answer #3: not chosen, ranked #2, score 0.11 (votes: 10, accepted: false, recency: 1.00, language match: false)"
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_when_min_score_is_set() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">3</div>
+                    <div class="post-text"><pre><code>low score</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">12</div>
+                    <div class="post-text"><pre><code>high score</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">-5</div>
+                    <div class="post-text"><pre><code>down voted</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_answers_per_question(3);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(format!("high score{}low score", ANSWER_SPLITTER))
+        );
+
+        conf.set_min_score(10);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from("high score"))
+        );
+
+        conf.set_min_score(100);
+        assert_eq!(parse_answer(page, &conf), None);
     }
-    None
-}
 
-/// make code block colorized.
-///
-/// Note that this function should only accept code block.
-fn colorized_code(code: String, possible_tags: &[String]) -> String {
-    let ss = SyntaxSet::load_defaults_newlines();
-    let ts: ThemeSet = ThemeSet::load_defaults();
-    let syntax: &SyntaxReference = guess_syntax(&possible_tags, &ss);
-    let mut h = HighlightLines::new(&syntax, &ts.themes["base16-eighties.dark"]);
-    let mut colorized: String = String::new();
+    #[test]
+    fn test_answer_to_text_when_header_is_hidden() {
+        let answer: Answer = Answer {
+            link: String::from("https://stackoverflow.com/questions/1/parse-json"),
+            title: String::from("parse json"),
+            question_tags: vec![String::from("json")],
+            asked: Some(String::from("2011-10-14")),
+            score: None,
+            accepted: false,
+            reputation: None,
+            answer_text: Some(String::from("json.loads(data)")),
+        };
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_show_question_meta(true);
+        assert_eq!(
+            answer.to_text(&conf),
+            "- Answer from https://stackoverflow.com/questions/1/parse-json [tags: json] [asked: 2011-10-14]\njson.loads(data)"
+        );
 
-    for line in LinesWithEndings::from(code.as_str()) {
-        let escaped = as_24_bit_terminal_escaped(&h.highlight(line, &ss), false);
-        colorized += escaped.as_str();
+        conf.set_show_header(false);
+        assert_eq!(answer.to_text(&conf), "json.loads(data)");
     }
-    colorized
-}
 
-fn guess_syntax<'a>(possible_tags: &[String], ss: &'a SyntaxSet) -> &'a SyntaxReference {
-    for tag in possible_tags {
-        let syntax = ss.find_syntax_by_token(tag.as_str());
-        if let Some(result) = syntax {
-            return result;
-        }
+    #[test]
+    fn test_answer_to_text_when_max_lines_is_set() {
+        let link: String = String::from("https://stackoverflow.com/questions/1/long-answer");
+        let answer: Answer = Answer {
+            link: link.clone(),
+            title: String::from("long answer"),
+            question_tags: vec![],
+            asked: None,
+            score: None,
+            accepted: false,
+            reputation: None,
+            answer_text: Some(
+                (1..=100)
+                    .map(|line| format!("line {}", line))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            ),
+        };
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_max_answer_lines(Some(3));
+        assert_eq!(
+            answer.to_text(&conf),
+            format!(
+                "- Answer from {}\nline 1\nline 2\nline 3\n… (truncated, see {})",
+                link, link
+            )
+        );
+
+        conf.set_max_answer_lines(Some(100));
+        assert!(answer.to_text(&conf).ends_with("\nline 99\nline 100"));
     }
-    ss.find_syntax_plain_text()
-}
 
-/// Return links from the given stackoverflow links.
-///
-///
-/// # Arguments
-///
-/// * `links` - stackoverflow links.
-///
-/// # Returns
-/// A list of links with splitter.  Which can directly output by the caller.
-fn answers_links_only(links: &[String], restricted_length: usize) -> String {
-    let mut results: Vec<String> = Vec::new();
-    let mut links_iter = links.iter();
-    for _ in 0..restricted_length {
-        let next_link = links_iter.next();
-        match next_link {
-            Some(link) => {
-                if !link.contains("question") {
-                    continue;
-                }
-                let url: Url = Url::parse(link)
-                    .expect("Parse url failed, if you receive this message, please fire an issue.");
+    #[test]
+    fn test_parse_answer_when_code_has_surrounding_blank_lines() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="post-text"><pre><button class="js-copy-button">Copy</button><code>
 
-                let answer: String =
-                    format!("Title - {}\n{}", extract_question(url.path()), *link,);
-                results.push(answer);
-            }
-            None => break,
-        }
-    }
-    results.join(SPLITTER)
-}
+fn main() {
 
-/// Extract question content.
-///
-/// # Examples
-///
-/// let question: String = extract_question("questions/user_id/the-specific-question");
-/// assert_eq!(question, String::from("the specific question"));
-fn extract_question(path: &str) -> String {
-    // The stack overflow question have the following format
-    // https://stackoverflow.com/questions/user_id/the-specific-question
-    // we want to extract the question part out.
-    let splitted: Vec<&str> = path.split('/').collect();
-    splitted[splitted.len() - 1].replace('-', " ")
+    println!("hors");
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::config::{Config, OutputOption};
+</code></pre></div>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert_eq!(
+            parse_answer(page, &conf),
+            Some(String::from("fn main() {\n\n    println!(\"hors\");\n}"))
+        );
+    }
 
     #[test]
-    fn test_answer_links_only() {
-        let links: Vec<String> = vec![String::from(
-            "https://stackoverflow.com/questions/test/how-to-write-function",
-        )];
-        let restricted_length: usize = 1;
-        let results: String = format!(
-            "Title - {}\n{}",
-            "how to write function",
-            "https://stackoverflow.com/questions/test/how-to-write-function"
+    fn test_parse_answer_when_show_comments() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="js-vote-count">7</div>
+                    <div class="post-text"><p>Use from_str.</p></div>
+                    <ul class="comments-list">
+                        <li class="comment" data-comment-score="2">
+                            <span class="comment-copy">Thanks!</span>
+                        </li>
+                        <li class="comment" data-comment-score="15">
+                            <span class="comment-copy">It panics on invalid json, use <code>?</code> instead.</span>
+                        </li>
+                    </ul>
+                </div>
+            </body>
+        </html>
+        "#,
+        );
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from("Use from_str."))
+        );
+
+        conf.set_show_comments(true);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from(
+                "Use from_str.\nComments:\n\
+                 - It panics on invalid json, use ? instead.\n\
+                 - Thanks!\n"
+            ))
+        );
+
+        // comments are omitted when the answer has none.
+        let page: String = page.replace("comment-copy", "comment-text");
+        assert_eq!(
+            parse_answer(page, &conf),
+            Some(String::from("Use from_str."))
         );
-        assert_eq!(answers_links_only(&links, restricted_length), results);
     }
 
     #[test]
-    fn test_answer_links_only_when_contains_links_is_not_question() {
-        let links: Vec<String> = vec![
-            String::from("https://stackoverflow.com/tags/rust"), // this link shouldn't output
-            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
-        ];
-        let restricted_length: usize = 4;
-        let results: String = format!(
-            "Title - {}\n{}",
-            "how to write function",
-            "https://stackoverflow.com/questions/test/how-to-write-function"
+    fn test_parse_answer_when_show_score() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer accepted-answer">
+                    <div class="js-vote-count" data-value="142">142</div>
+                    <div class="post-text"><p>use serde</p></div>
+                </div>
+            </body>
+        </html>
+        "#,
         );
-        assert_eq!(answers_links_only(&links, restricted_length), results);
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_show_score(true);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from("[score: 142] [accepted]\nuse serde"))
+        );
+
+        // code is output as it is.
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_show_score(true);
+        conf.set_code_mode_prose_fallback(true);
+        assert_eq!(parse_answer(page, &conf), Some(String::from("use serde")));
     }
 
     #[test]
-    fn test_answer_links_only_when_restricted_size_is_less_than_given_links() {
-        let links: Vec<String> = vec![
-            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
-            String::from("https://stackoverflow.com/questions/test/best-practise-for-rust"),
-        ];
-        let restricted_length: usize = 1;
-        let results: String = format!(
-            "Title - {}\n{}",
-            "how to write function",
-            "https://stackoverflow.com/questions/test/how-to-write-function"
+    fn test_parse_answer_when_show_score_of_unscored_answer() {
+        let page: String = String::from(
+            r#"
+        <html>
+            <body>
+                <div class="answer">
+                    <div class="post-text"><p>unscored</p></div>
+                </div>
+                <div class="answer accepted-answer">
+                    <div class="post-text"><p>accepted</p></div>
+                </div>
+            </body>
+        </html>
+        "#,
         );
-        assert_eq!(answers_links_only(&links, restricted_length), results);
+        let mut conf: Config = Config::new(OutputOption::All, 2, false);
+        conf.set_show_score(true);
+        conf.set_prefer_accepted(true);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from("[accepted]\naccepted"))
+        );
+        let doc: Document = Document::from(page.as_str());
+        let unscored: Node = doc.find(Class("answer")).next().unwrap();
+        assert_eq!(score_header(unscored), None);
     }
 
     #[test]
-    fn test_answer_links_only_when_restricted_size_is_large_than_given_links() {
-        let links: Vec<String> = vec![
-            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
-            String::from("https://stackoverflow.com/questions/test/best-practise-for-rust"),
-        ];
-        let restricted_length: usize = 1000;
-        let results: String = format!(
-            "{}\n{}{}{}\n{}",
-            "Title - how to write function",
-            "https://stackoverflow.com/questions/test/how-to-write-function",
-            SPLITTER,
-            "Title - best practise for rust",
-            "https://stackoverflow.com/questions/test/best-practise-for-rust"
-        );
-        assert_eq!(answers_links_only(&links, restricted_length), results);
+    fn test_parse_count() {
+        assert_eq!(parse_count("42"), Some(42));
+        assert_eq!(parse_count(" 1,024 "), Some(1024));
+        assert_eq!(parse_count("12.3k"), Some(12_300));
+        assert_eq!(parse_count("1.2m"), Some(1_200_000));
+        assert_eq!(parse_count("reputation"), None);
     }
 
     #[test]
-    fn test_parse_answer() {
+    fn test_parse_answer_when_page_order_differs_from_votes() {
+        // the page is sorted by "Oldest" tab.
         let page: String = String::from(
             r#"
         <html>
             <body>
                 <div class="answer">
-                    <div class="js-vote-count">130</div>
-                    <div class="post-text">
-                        <pre>
-                            <code>println!("hello world")</code>
-                        </pre>
-                    </div>
+                    <div class="js-vote-count" data-value="3">3</div>
+                    <div class="post-text"><pre><code>oldest</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count" data-value="1500">1.5k</div>
+                    <div class="post-text"><pre><code>not accepted</code></pre></div>
+                </div>
+                <div class="answer accepted-answer">
+                    <div class="js-vote-count" data-value="1500">1.5k</div>
+                    <div class="post-text"><pre><code>accepted</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count" data-value="20">20</div>
+                    <div class="post-text"><pre><code>newest</code></pre></div>
                 </div>
             </body>
         </html>
         "#,
         );
         let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
-        let answer: Option<String> = parse_answer(page, &conf);
-
-        assert_eq!(answer.is_some(), true);
-
-        if let Some(code) = answer {
-            assert_eq!(code.trim(), String::from(r#"println!("hello world")"#));
-        }
+        assert_eq!(parse_answer(page, &conf), Some(String::from("accepted")));
     }
 
     #[test]
-    fn test_parse_answer_when_pre_and_code_both_existed() {
+    fn test_parse_answer_when_prefer_accepted() {
         let page: String = String::from(
             r#"
         <html>
             <body>
                 <div class="answer">
-                    <div class="js-vote-count">130</div>
-                    <div class="post-text">
-                        <p>answer <code>goto</code> here </p>
-                        <pre>
-                            <code>println!("hello world")</code>
-                        </pre>
-                    </div>
+                    <div class="js-vote-count">300</div>
+                    <div class="post-text"><pre><code>most voted</code></pre></div>
+                </div>
+                <div class="answer accepted-answer">
+                    <div class="js-vote-count">0</div>
+                    <div class="post-text"><pre><code>accepted</code></pre></div>
                 </div>
             </body>
         </html>
         "#,
         );
-        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
-        let answer: Option<String> = parse_answer(page, &conf);
-
-        assert_eq!(answer.is_some(), true);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(String::from("most voted"))
+        );
 
-        if let Some(code) = answer {
-            assert_eq!(code.trim(), String::from(r#"println!("hello world")"#));
-        }
+        conf.set_prefer_accepted(true);
+        assert_eq!(parse_answer(page, &conf), Some(String::from("accepted")));
     }
 
     #[test]
-    fn test_parse_answer_when_no_code_available() {
+    fn test_parse_answer_when_prefer_accepted_but_no_answer_is_accepted() {
         let page: String = String::from(
             r#"
         <html>
             <body>
                 <div class="answer">
-                    <div class="js-vote-count">130</div>
-                    <div class="post-text">
-                        <p>answer here </p>
-                    </div>
+                    <div class="js-vote-count">3</div>
+                    <div class="post-text"><pre><code>less voted</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">300</div>
+                    <div class="post-text"><pre><code>most voted</code></pre></div>
                 </div>
             </body>
         </html>
         "#,
         );
-        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
-        let answer: Option<String> = parse_answer(page, &conf);
-
-        assert_eq!(answer.is_none(), true);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_prefer_accepted(true);
+        assert_eq!(parse_answer(page, &conf), Some(String::from("most voted")));
     }
 
     #[test]
-    fn test_parse_answer_when_only_code_existed() {
+    fn test_parse_answer_when_multiple_answers_per_question() {
         let page: String = String::from(
             r#"
         <html>
             <body>
                 <div class="answer">
-                    <div class="js-vote-count">130</div>
-                    <div class="post-text">
-                        <p>answer <code>goto</code> here </p>
-                    </div>
+                    <div class="js-vote-count">20</div>
+                    <div class="post-text"><pre><code>second</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="post-text"><pre><code>no votes</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">5</div>
+                    <div class="post-text"><pre><code>third</code></pre></div>
+                </div>
+                <div class="answer">
+                    <div class="js-vote-count">300</div>
+                    <div class="post-text"><pre><code>first</code></pre></div>
                 </div>
             </body>
         </html>
         "#,
         );
-        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
-        let answer: Option<String> = parse_answer(page, &conf);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_answers_per_question(2);
+        assert_eq!(
+            parse_answer(page.clone(), &conf),
+            Some(format!("first{}second", ANSWER_SPLITTER))
+        );
 
-        assert_eq!(answer.is_some(), true);
+        conf.set_answers_per_question(10);
+        assert_eq!(
+            parse_answer(page, &conf),
+            Some(format!(
+                "first{}second{}third{}no votes",
+                ANSWER_SPLITTER, ANSWER_SPLITTER, ANSWER_SPLITTER
+            ))
+        );
+    }
 
-        if let Some(code) = answer {
-            assert_eq!(code.trim(), String::from("goto"));
-        }
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(parse_day("1970-01-01 00:00:00Z"), Some(0));
+        assert_eq!(parse_day("2000-03-01T00:00:00"), Some(11017));
+        assert_eq!(parse_day("2011-10-14 16:58:14Z"), Some(15261));
+        assert_eq!(parse_day("yesterday"), None);
+        assert_eq!(parse_day("2011-13-14 16:58:14Z"), None);
+    }
+
+    #[test]
+    fn test_bound_page() {
+        let page: &str = r#"<html><head><script>var a = "<div>";</script><style>p {}</style></head>
+<body><div id="mainbar"><div class="answer">answer</div></div>
+<div id="sidebar"><div class="answer">sidebar</div></div></body></html>"#;
+        assert_eq!(
+            bound_page(page),
+            r#"<html><head></head>
+<body><div id="mainbar"><div class="answer">answer</div></div>
+"#
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_when_bounded_parse_on_large_page() {
+        let answer: &str = r#"
+                <div class="answer">
+                    <div class="js-vote-count">130</div>
+                    <div class="post-text"><p>bounded answer</p></div>
+                </div>"#;
+        let script: String = format!("<script>{}</script>", "var x = 1;".repeat(50_000));
+        let sidebar: String = format!(
+            r#"<div id="sidebar">{}</div>"#,
+            r#"<div class="module"><a href="/q/1">linked question</a></div>"#.repeat(20_000)
+        );
+        let page: String = format!(
+            "<html><head>{}</head><body><div id=\"mainbar\">{}</div>{}</body></html>",
+            script, answer, sidebar
+        );
+
+        let conf: Config = Config::new(OutputOption::All, 1, false);
+        let (unbounded_answer, unbounded_peak) =
+            peak_allocated(|| parse_answer(page.clone(), &conf));
+        let mut conf: Config = Config::new(OutputOption::All, 1, false);
+        conf.set_bounded_parse(true);
+        let (bounded_answer, bounded_peak) = peak_allocated(|| parse_answer(page.clone(), &conf));
+
+        assert_eq!(bounded_answer, unbounded_answer);
+        assert_eq!(bounded_answer.unwrap().trim(), "bounded answer");
+        // the page itself is cloned, so the peak should be a little larger than page size.
+        assert!(bounded_peak < page.len() * 2, "peak is {}", bounded_peak);
+        assert!(
+            bounded_peak * 4 < unbounded_peak,
+            "bounded {} unbounded {}",
+            bounded_peak,
+            unbounded_peak
+        );
     }
 
     #[test]
@@ -536,7 +4564,7 @@ mod test {
         let conf: Config = Config::new(OutputOption::All, 1, false);
         let answer: Option<String> = parse_answer(page, &conf);
 
-        assert_eq!(answer.is_some(), true);
+        assert!(answer.is_some());
 
         if let Some(code) = answer {
             assert_eq!(code.trim(), String::from("answer goto here"));
@@ -562,7 +4590,7 @@ mod test {
         let conf: Config = Config::new(OutputOption::All, 1, false);
         let answer: Option<String> = parse_answer(page, &conf);
 
-        assert_eq!(answer.is_some(), true);
+        assert!(answer.is_some());
 
         if let Some(code) = answer {
             assert_eq!(code.trim(), String::from("answer goto here"));
@@ -590,7 +4618,7 @@ mod test {
         let conf: Config = Config::new(OutputOption::All, 1, false);
         let answer: Option<String> = parse_answer(page, &conf);
 
-        assert_eq!(answer.is_some(), true);
+        assert!(answer.is_some());
 
         if let Some(code) = answer {
             assert_eq!(code.trim(), String::from("print('go go go')"));
@@ -622,7 +4650,7 @@ mod test {
         let conf: Config = Config::new(OutputOption::All, 1, false);
         let answer: Option<String> = parse_answer(page, &conf);
 
-        assert_eq!(answer.is_some(), true);
+        assert!(answer.is_some());
 
         if let Some(code) = answer {
             assert_eq!(code.trim(), String::from("answer higher here"));
@@ -772,7 +4800,7 @@ mod test {
         let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
         let answer: Option<String> = parse_answer(page, &conf);
 
-        assert_eq!(answer.is_none(), true);
+        assert!(answer.is_none());
     }
 
     #[test]
@@ -780,18 +4808,94 @@ mod test {
         let page: String = String::from("");
         let conf: Config = Config::new(OutputOption::All, 1, false);
         let answer: Option<String> = parse_answer(page, &conf);
-        assert_eq!(answer.is_none(), true);
+        assert!(answer.is_none());
     }
 
     #[test]
     fn test_extract_question() {
-        let question: String = extract_question("questions/user_id/the-specific-question");
-        assert_eq!(question, String::from("the specific question"));
+        let question: Option<String> = extract_question("questions/user_id/the-specific-question");
+        assert_eq!(question, Some(String::from("the specific question")));
     }
 
     #[test]
     fn test_extract_question_when_question_contains_one_word() {
-        let question: String = extract_question("questions/user_id/question");
-        assert_eq!(question, String::from("question"));
+        let question: Option<String> = extract_question("questions/user_id/question");
+        assert_eq!(question, Some(String::from("question")));
+    }
+
+    #[test]
+    fn test_extract_question_when_path_is_odd() {
+        assert_eq!(
+            extract_question("/questions/user_id/the-specific-question/"),
+            Some(String::from("the specific question"))
+        );
+        assert_eq!(extract_question("/"), None);
+        assert_eq!(extract_question(""), None);
+        assert_eq!(extract_question("//"), None);
+        assert_eq!(
+            extract_question("/questions/user_id/the-specific-question/?answertab=votes#tab-top"),
+            Some(String::from("the specific question"))
+        );
+        // the question id isn't a title.
+        assert_eq!(extract_question("/questions/123"), None);
+        assert_eq!(extract_question("/questions/123/"), None);
+        assert_eq!(extract_question("/q/123?noredirect=1"), None);
+    }
+
+    #[test]
+    fn test_extract_question_when_slug_is_encoded() {
+        assert_eq!(
+            extract_question("/questions/1/how-to-parse-c%2B%2B"),
+            Some(String::from("how to parse c++"))
+        );
+        assert_eq!(
+            extract_question("/questions/2/what%27s-the-difference-of-%3D%3D-and-%3D%3D%3D"),
+            Some(String::from("what's the difference of == and ==="))
+        );
+        assert_eq!(
+            extract_question("/questions/3/caf%C3%A9-and-utf%2D8"),
+            Some(String::from("café and utf-8"))
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only_when_link_has_no_slug() {
+        // broken search result which only mentions questions in its query string.
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/?tab=/questions/"),
+            String::from("https://stackoverflow.com/questions/1/parse-json"),
+        ];
+        assert_eq!(
+            answers_links_only(&links, 2, SPLITTER),
+            "Title - parse json\nhttps://stackoverflow.com/questions/1/parse-json"
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only_of_other_sites() {
+        let links: Vec<String> = vec![
+            String::from("https://superuser.com/tags/ssh"),
+            String::from("https://superuser.com/questions/1/how-to-ssh"),
+            String::from("https://unix.stackexchange.com/q/2"),
+        ];
+        assert_eq!(
+            answers_links_only(&links, 3, SPLITTER),
+            format!(
+                "Title - how to ssh\nhttps://superuser.com/questions/1/how-to-ssh{}https://unix.stackexchange.com/q/2",
+                SPLITTER
+            )
+        );
+    }
+
+    #[test]
+    fn test_answer_links_only_when_link_is_invalid() {
+        let links: Vec<String> = vec![
+            String::from("not a url/questions/-at-all"),
+            String::from("https://stackoverflow.com/questions/test/how-to-write-function"),
+        ];
+        assert_eq!(
+            answers_links_only(&links, 2, SPLITTER),
+            "Title - how to write function\nhttps://stackoverflow.com/questions/test/how-to-write-function"
+        );
     }
 }
@@ -1,6 +1,7 @@
 use crate::error::Result;
+use crate::paths;
+use crate::utils::canonical_question_url;
 use bincode::{deserialize_from, serialize_into};
-use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{create_dir_all, File, OpenOptions};
@@ -59,10 +60,9 @@ impl AnswerRecordsCache {
     /// Return the instance of AnswerRecordsCache.  Error will be returned if
     /// loading local cache file failed.
     pub fn load() -> Result<AnswerRecordsCache> {
-        if let Some(base_dirs) = BaseDirs::new() {
+        if let Some(dir) = paths::cache_dir() {
             // just create cache file if not existed, and deserialize it.
-            let dir = base_dirs.cache_dir().to_path_buf();
-            let cache_file: PathBuf = AnswerRecordsCache::create_file_if_not_existed(&dir)?;
+            let cache_file: PathBuf = AnswerRecordsCache::create_file_if_not_existed(dir)?;
             let f = File::open(cache_file)?;
             let answer_records: AnswerRecordsCache = deserialize_from(f)?;
             return Ok(answer_records);
@@ -88,7 +88,7 @@ impl AnswerRecordsCache {
     /// # Returns
     /// Return cached page if we can find it and it's not too old, else returns None.
     pub fn get(&self, link: &str) -> Option<&String> {
-        let possible_page: Option<&AnswerRecord> = self.0.get(link);
+        let possible_page: Option<&AnswerRecord> = self.0.get(&cache_key(link));
         match possible_page {
             // if we can find relative record
             Some(record) => {
@@ -115,8 +115,9 @@ impl AnswerRecordsCache {
     /// * `link` - link to page.
     /// * `page` - the content of page.
     pub fn put(&mut self, link: String, page: String) {
-        let record: AnswerRecord = AnswerRecord::new(link.clone(), page);
-        self.0.insert(link, record);
+        let key: String = cache_key(&link);
+        let record: AnswerRecord = AnswerRecord::new(link, page);
+        self.0.insert(key, record);
     }
 
     /// Save the data into local file.
@@ -130,9 +131,8 @@ impl AnswerRecordsCache {
         if MAX_SIZE < self.0.len() {
             // TODO: truncate it to have size MAX_SIZE
         }
-        if let Some(base_dirs) = BaseDirs::new() {
-            let dir = base_dirs.cache_dir();
-            let cache_path: PathBuf = dir.join("hors").join("answers");
+        if let Some(dir) = paths::cache_dir() {
+            let cache_path: PathBuf = dir.join("answers");
             let f = OpenOptions::new()
                 .write(true)
                 .truncate(true)
@@ -143,8 +143,7 @@ impl AnswerRecordsCache {
         Ok(())
     }
 
-    fn create_file_if_not_existed(dir: &PathBuf) -> Result<PathBuf> {
-        let cache_directory: PathBuf = dir.join("hors");
+    fn create_file_if_not_existed(cache_directory: PathBuf) -> Result<PathBuf> {
         if !cache_directory.exists() {
             create_dir_all(&cache_directory).unwrap();
         }
@@ -157,6 +156,11 @@ impl AnswerRecordsCache {
     }
 }
 
+/// Different links to the same question should share the same record.
+fn cache_key(link: &str) -> String {
+    canonical_question_url(link).unwrap_or_else(|| link.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,7 +188,7 @@ mod tests {
             .expect("Time went beckwards")
             .as_secs();
         let test_record = AnswerRecord::new(link, page);
-        assert_eq!(test_record.is_too_old(current_time), false);
+        assert!(!test_record.is_too_old(current_time));
 
         let half_month_and_one_second: u64 = 3600 * 24 * 15 + 1;
         let time_after_half_month: u64 = SystemTime::now()
@@ -192,13 +196,13 @@ mod tests {
             .expect("Time went beckwards")
             .as_secs()
             + half_month_and_one_second;
-        assert_eq!(test_record.is_too_old(time_after_half_month), true);
+        assert!(test_record.is_too_old(time_after_half_month));
     }
 
     #[test]
     fn test_answer_record_cache_empty() {
         let record_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
-        assert_eq!(record_cache.0.is_empty(), true);
+        assert!(record_cache.0.is_empty());
     }
 
     #[test]
@@ -235,11 +239,21 @@ mod tests {
     #[test]
     fn test_answer_record_get_when_key_is_not_existed() {
         let record_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
+        assert!(record_cache
+            .get(&String::from("http://test_link"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_answer_record_get_when_link_is_variant_of_cached_link() {
+        let mut record_cache: AnswerRecordsCache = AnswerRecordsCache::load_empty();
+        record_cache.put(
+            "https://stackoverflow.com/questions/1/test-question".to_string(),
+            "<html></html>".to_string(),
+        );
         assert_eq!(
-            record_cache
-                .get(&String::from("http://test_link"))
-                .is_none(),
-            true
+            record_cache.get("http://stackoverflow.com/questions/1/test-question#answer-2"),
+            Some(&String::from("<html></html>"))
         );
     }
 }
@@ -0,0 +1,215 @@
+//! Make the output fit into user's terminal, so it doesn't scroll off screen.
+use terminal_size::{terminal_size, Height, Width};
+
+/// Truncate the output to the height of current terminal.
+///
+/// The output is returned as it is if hors is not running in a terminal.
+pub fn fit_to_terminal(output: String) -> String {
+    match terminal_size() {
+        Some((Width(width), Height(height))) => fit_to_size(output, width, height),
+        None => output,
+    }
+}
+
+/// Truncate the output to the given terminal size.
+///
+/// The beginning of output is kept, because the most relevant answer goes
+/// first.  Lines which are longer than terminal width take more than one row,
+/// and ANSI escapes are not counted as they are invisible.
+///
+/// # Returns
+///
+/// The truncated output which ends with a `…N more lines` footer, or the
+/// output itself if it already fits.
+fn fit_to_size(output: String, width: u16, height: u16) -> String {
+    let width: usize = width.max(1) as usize;
+    let height: usize = height as usize;
+    let lines: Vec<&str> = output.lines().collect();
+    let rows: Vec<usize> = lines
+        .iter()
+        .map(|line| visible_width(line).div_ceil(width).max(1))
+        .collect();
+    if rows.iter().sum::<usize>() <= height {
+        return output;
+    }
+
+    // keep one row for the footer.
+    let mut used: usize = 0;
+    let kept: usize = rows
+        .iter()
+        .take_while(|row| {
+            used += *row;
+            used < height
+        })
+        .count();
+    let mut truncated: String = lines[..kept].join("\n");
+    if output.contains('\x1b') {
+        // don't let the colors of truncated code leak to the footer.
+        truncated.push_str("\x1b[0m");
+    }
+    format!("{}\n…{} more lines", truncated, lines.len() - kept)
+}
+
+/// Truncate the answer to at most `max_lines` lines and `max_chars` visible
+/// characters.
+///
+/// It works on colorized answer, ANSI escapes are neither split nor counted,
+/// and colors are reset after the truncated answer, so they don't leak to the
+/// following output.
+///
+/// # Returns
+///
+/// The truncated answer, or None if it already fits.
+pub fn truncate_answer(
+    answer: &str,
+    max_lines: Option<usize>,
+    max_chars: Option<usize>,
+) -> Option<String> {
+    let mut truncated: String = String::with_capacity(answer.len());
+    let mut lines: usize = 1;
+    let mut visible: usize = 0;
+    let mut chars = answer.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if c == '\x1b' {
+            truncated.push(c);
+            if let Some((_, '[')) = chars.clone().next() {
+                chars.next();
+                truncated.push('[');
+                // copy the whole CSI sequence, it ends at a byte in range `@` to `~`.
+                for (_, c) in &mut chars {
+                    truncated.push(c);
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        let exceeded: bool = if c == '\n' {
+            lines += 1;
+            max_lines.is_some_and(|max_lines| lines > max_lines)
+        } else {
+            visible += 1;
+            max_chars.is_some_and(|max_chars| visible > max_chars)
+        };
+        if exceeded {
+            // the rest may be a trailing newline only.
+            if answer[index..].trim().is_empty() {
+                return None;
+            }
+            let mut truncated: String = truncated.trim_end().to_string();
+            if answer.contains('\x1b') {
+                truncated.push_str("\x1b[0m");
+            }
+            return Some(truncated);
+        }
+        truncated.push(c);
+    }
+    None
+}
+
+/// Count characters which are displayed in terminal, ANSI escapes like
+/// `\x1b[38;2;1;2;3m` are skipped.
+fn visible_width(line: &str) -> usize {
+    let mut width: usize = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                // CSI sequence ends at a byte in range `@` to `~`.
+                for c in &mut chars {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        width += 1;
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_to_size_when_output_fits() {
+        let output: String = String::from("line 1\nline 2\nline 3");
+        assert_eq!(fit_to_size(output.clone(), 80, 3), output);
+    }
+
+    #[test]
+    fn test_fit_to_size_when_output_is_too_long() {
+        let output: String = String::from("line 1\nline 2\nline 3\nline 4\nline 5");
+        assert_eq!(
+            fit_to_size(output, 80, 3),
+            String::from("line 1\nline 2\n…3 more lines")
+        );
+    }
+
+    #[test]
+    fn test_fit_to_size_when_line_is_wrapped() {
+        let output: String = format!("{}\nline 2\nline 3", "a".repeat(15));
+        assert_eq!(
+            fit_to_size(output, 10, 3),
+            format!("{}\n…2 more lines", "a".repeat(15))
+        );
+    }
+
+    #[test]
+    fn test_fit_to_size_when_output_is_colorized() {
+        // each line is 10 chars visible, even if it's much longer with escapes.
+        let line: String = String::from("\x1b[38;2;249;38;114mprintln!()\x1b[0m");
+        let output: String = [line.as_str(); 4].join("\n");
+        assert_eq!(fit_to_size(output.clone(), 10, 4), output);
+        assert_eq!(
+            fit_to_size(output, 10, 3),
+            format!("{}\n{}\x1b[0m\n…2 more lines", line, line)
+        );
+    }
+
+    #[test]
+    fn test_truncate_answer() {
+        let answer: &str = "line 1\nline 2\nline 3\n";
+        assert_eq!(
+            truncate_answer(answer, Some(2), None),
+            Some(String::from("line 1\nline 2"))
+        );
+        assert_eq!(truncate_answer(answer, Some(3), None), None);
+        assert_eq!(truncate_answer(answer, None, None), None);
+        assert_eq!(
+            truncate_answer(answer, None, Some(9)),
+            Some(String::from("line 1\nlin"))
+        );
+        assert_eq!(
+            truncate_answer(answer, Some(2), Some(9)),
+            Some(String::from("line 1\nlin"))
+        );
+    }
+
+    #[test]
+    fn test_truncate_answer_when_it_is_colorized() {
+        let line: &str = "\x1b[38;2;249;38;114mfn\x1b[38;2;248;248;242m main() {}";
+        let answer: String = [line; 3].join("\n");
+        assert_eq!(
+            truncate_answer(&answer, Some(1), None),
+            Some(format!("{}\x1b[0m", line))
+        );
+        // escapes are kept whole, and they are not counted.
+        assert_eq!(
+            truncate_answer(&answer, None, Some(4)),
+            Some(String::from(
+                "\x1b[38;2;249;38;114mfn\x1b[38;2;248;248;242m m\x1b[0m"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_visible_width() {
+        assert_eq!(visible_width("hors"), 4);
+        assert_eq!(visible_width("\x1b[48;2;39;40;34mhors\x1b[0m"), 4);
+        assert_eq!(visible_width("…"), 1);
+    }
+}
@@ -2,11 +2,13 @@
 extern crate log;
 
 use clap::{self, Clap};
-use hors::{self, Config, OutputOption, Result, SearchEngine};
+use hors::{self, ColorDepth, Config, Error, OutputOption, Rank, Result, SearchEngine};
 use reqwest::{Client, ClientBuilder};
 
+use std::path::PathBuf;
 use std::process;
 use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Clap)]
 #[clap(version = env!("CARGO_PKG_VERSION"), author = env!("CARGO_PKG_AUTHORS"), about = env!("CARGO_PKG_DESCRIPTION"))]
@@ -17,6 +19,15 @@ struct Opts {
     link: bool,
     #[clap(short, long, about("make raw output (not colorized)."))]
     raw: bool,
+    #[clap(
+        long,
+        about("colorize output even if it's not a terminal or `NO_COLOR` is set.")
+    )]
+    color: bool,
+    #[clap(long, about("display answers as json."))]
+    json: bool,
+    #[clap(long, about("display answers as markdown."))]
+    markdown: bool,
     #[clap(short, long, default_value = "1", about("number of answers to return."))]
     number_answers: u8,
     #[clap(
@@ -24,23 +35,203 @@ struct Opts {
         long,
         default_value = "duckduckgo",
         env = "HORS_ENGINE",
-        about("select middle search engine, currently support `bing`, `google` and `duckduckgo`.")
+        about("select middle search engine, currently support `bing`, `google`, `duckduckgo` and `stackexchange`.")
     )]
     engine: String,
     #[clap(short, long, about("Disable system proxy."))]
     disable_proxy: bool,
+    #[clap(
+        long,
+        about("proxy of all requests, e.g. `socks5://127.0.0.1:1080`, default to `HTTP_PROXY` and `HTTPS_PROXY`.")
+    )]
+    proxy: Option<String>,
+    #[clap(
+        long,
+        parse(from_os_str),
+        about("load `.sublime-syntax` files in the directory to colorize code.")
+    )]
+    syntax_dir: Option<PathBuf>,
+    #[clap(
+        long,
+        requires("syntax-dir"),
+        about(
+            "only use syntaxes in `--syntax-dir`, rather than merge them with the default ones."
+        )
+    )]
+    replace_syntaxes: bool,
+    #[clap(long, about("only match the query in question title."))]
+    in_title: bool,
+    #[clap(long, about("language of search results, e.g. `en`, only for google."))]
+    locale: Option<String>,
+    #[clap(long, about("region of search results, e.g. `us`, only for google."))]
+    region: Option<String>,
+    #[clap(
+        long,
+        parse(from_os_str),
+        about("save fetched question pages into the directory.")
+    )]
+    save_html: Option<PathBuf>,
+    #[clap(
+        long,
+        about("get question page from Wayback Machine when stackoverflow is down.")
+    )]
+    wayback: bool,
+    #[clap(long, about("only select answer which contains the keyword."))]
+    must_contain: Option<String>,
+    #[clap(
+        long,
+        number_of_values = 1,
+        about("host which should be removed from search results, can be used multiple times.")
+    )]
+    block_host: Vec<String>,
+    #[clap(
+        long,
+        about("rank answers written in the language first, e.g. `rust`, `py`.")
+    )]
+    prefer_lang: Option<String>,
+    #[clap(
+        long,
+        about("prefer answers edited in or after the year, e.g. `2018`.")
+    )]
+    edited_after: Option<u16>,
+    #[clap(long, about("explain how answers are ranked."))]
+    explain_ranking: bool,
+    #[clap(long, about("truncate the output to the height of terminal."))]
+    fit: bool,
+    #[clap(long, about("truncate each answer to the number of lines."))]
+    max_lines: Option<usize>,
+    #[clap(long, about("truncate each answer to the number of characters."))]
+    max_chars: Option<usize>,
+    #[clap(
+        long,
+        about("don't accept stackoverflow's cookie consent automatically.")
+    )]
+    no_consent: bool,
+    #[clap(
+        long,
+        about("don't display the `- Answer from <link>` line of answers.")
+    )]
+    no_header: bool,
+    #[clap(long, about("display reputation of the answer's author."))]
+    reputation: bool,
+    #[clap(long, about("display tags and ask date of the question."))]
+    meta: bool,
+    #[clap(long, about("display top comments under the answer, only for --all."))]
+    comments: bool,
+    #[clap(
+        long,
+        default_value = "0",
+        allow_hyphen_values = true,
+        about("skip answers which score below it.")
+    )]
+    min_score: i16,
+    #[clap(long, about("display score of the answer, and if it's accepted."))]
+    score: bool,
+    #[clap(
+        long,
+        number_of_values = 1,
+        about("StackExchange site to search within, can be used multiple times, default to `stackoverflow.com`.")
+    )]
+    site: Vec<String>,
+    #[clap(
+        long,
+        about("optimize for latency: only the top link, short timeout and no retry.")
+    )]
+    fast: bool,
+    #[clap(
+        long,
+        about("only print the query urls and the extracted links, answers aren't fetched.")
+    )]
+    dry_run: bool,
+    #[clap(
+        long,
+        about("separator between answers, `\\n` is a newline, e.g. `\\n---\\n`.")
+    )]
+    separator: Option<String>,
+    #[clap(long, about("list linked and related questions of the top question."))]
+    related: bool,
+    #[clap(long, about("stop when search results seem unrelated to the query."))]
+    confidence_guard: bool,
+    #[clap(long, about("pipe the output through the command, e.g. `bat -l md`."))]
+    formatter: Option<String>,
+    #[clap(
+        long,
+        about("key of Stack Exchange API, which raises the daily quota.")
+    )]
+    api_key: Option<String>,
+    #[clap(long, about("select the accepted answer before others."))]
+    accepted: bool,
+    #[clap(
+        long,
+        default_value = "1",
+        about("number of top voted answers to return for each question.")
+    )]
+    answers_per_question: u8,
+    #[clap(
+        long,
+        about(
+            "colors to colorize code, `truecolor` or `256`, default to detect from `COLORTERM`."
+        )
+    )]
+    color_depth: Option<String>,
+    #[clap(
+        long,
+        about("language to highlight code, e.g. `rust`, rather than guessing from question tags.")
+    )]
+    highlight: Option<String>,
+    #[clap(
+        long,
+        about("theme to colorize code, e.g. `InspiredGitHub` for light terminals.")
+    )]
+    theme: Option<String>,
+    #[clap(
+        long,
+        parse(from_os_str),
+        about("`.tmTheme` file to colorize code, it overrides `--theme`.")
+    )]
+    theme_file: Option<PathBuf>,
+    #[clap(
+        long,
+        about("cache answers on disk, so the same query is answered offline.")
+    )]
+    cache: bool,
+    #[clap(long, about("ignore the cached answers and search again."))]
+    refresh: bool,
+    #[clap(
+        long,
+        default_value = "10",
+        about("seconds before a request times out, 0 means never.")
+    )]
+    timeout: u64,
+    #[clap(
+        long,
+        default_value = "2",
+        about("times to retry a request on timeout, 5xx or 429.")
+    )]
+    retries: u32,
+    #[clap(long, about("check if the supported search engines are reachable."))]
+    doctor: bool,
+    #[clap(long, about("log each network step to stderr."))]
+    verbose: bool,
     query: Vec<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let opts: Opts = Opts::parse();
+    init_logger(opts.verbose);
 
     let search_engine = SearchEngine::from_str(&opts.engine)?;
     debug!("Search under the {:?}", search_engine);
 
+    let conf: Config = init_config(&opts).unwrap_or_else(|err| {
+        eprintln!("Invalid config: {}", err);
+        process::exit(1);
+    });
+    debug!("User config: {:?}", conf);
+
     // Initialize reqwest::Client instance.
-    let mut client_builder: ClientBuilder = reqwest::ClientBuilder::new().cookie_store(true);
+    let mut client_builder: ClientBuilder = hors::utils::client_builder(&conf)?;
     if opts.disable_proxy {
         println!("disable proxy");
         client_builder = client_builder.no_proxy();
@@ -50,36 +241,141 @@ async fn main() -> Result<()> {
         process::exit(1);
     });
 
-    let target_links: Vec<String> =
-        hors::search_links_with_client(&opts.query.join(" "), search_engine, &client)
+    if opts.doctor {
+        doctor(&client).await;
+        return Ok(());
+    }
+
+    let answers: String =
+        hors::search_answers_with_client(&opts.query.join(" "), search_engine, conf, &client)
             .await
             .unwrap_or_else(|err| {
-                eprintln!("Search for target link failed: {}", err);
+                match err {
+                    Error::LowConfidence(links) => {
+                        eprintln!("The query seems too vague, please refine it. Best guesses:");
+                        for link in links {
+                            eprintln!("{}", link);
+                        }
+                    }
+                    Error::RateLimited(link) => eprintln!(
+                        "The search engine rate limits requests ({}), please try again later or switch to another engine.",
+                        link
+                    ),
+                    Error::NoAnswer(_) => {
+                        eprintln!("No answer is found, please try another query.")
+                    }
+                    err => eprintln!("Hors is running to error: {}", err),
+                }
                 process::exit(1);
             });
-
-    let conf: Config = init_config(&opts);
-    debug!("User config: {:?}", conf);
-    let answers: String = hors::get_answers_with_client(&target_links, conf, &client)
-        .await
-        .unwrap_or_else(|err| {
-            eprintln!("Hors is running to error: {}", err);
-            process::exit(1);
-        });
     println!("{}", answers);
 
     Ok(())
 }
 
+/// check all supported search engines, and output their health.
+async fn doctor(client: &Client) {
+    for name in &["bing", "google", "duckduckgo", "stackexchange"] {
+        let search_engine = SearchEngine::from_str(name).unwrap();
+        match hors::check_engine_with_client(search_engine, client).await {
+            Ok(health) => println!(
+                "{}: {:?} ({} ms)",
+                name,
+                health.status(),
+                health.latency().as_millis()
+            ),
+            Err(err) => println!("{}: check failed, {}", name, err),
+        }
+    }
+}
+
+/// Log to stderr, the level is read from `RUST_LOG`, e.g: `RUST_LOG=hors=debug`.
+/// With `--verbose`, the network steps of hors are always logged.
+fn init_logger(verbose: bool) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if verbose {
+        builder.filter_module("hors", log::LevelFilter::Info);
+    }
+    builder.init();
+}
+
 /// initialize config from user input arguments.
-fn init_config(opts: &Opts) -> Config {
+fn init_config(opts: &Opts) -> Result<Config> {
     let output_option = if opts.link {
         OutputOption::Links
+    } else if opts.json {
+        OutputOption::Json
+    } else if opts.markdown {
+        OutputOption::Markdown
     } else if opts.all {
         OutputOption::All
     } else {
         OutputOption::OnlyCode
     };
 
-    Config::new(output_option, opts.number_answers, !opts.raw)
+    let colorize: bool = if opts.raw {
+        false
+    } else {
+        opts.color || hors::config::detect_colorize()
+    };
+    let mut conf: Config = Config::new(output_option, opts.number_answers, colorize);
+    conf.set_match_in_title(opts.in_title);
+    conf.set_search_locale(opts.locale.clone());
+    conf.set_search_region(opts.region.clone());
+    conf.set_save_html_dir(opts.save_html.clone());
+    conf.set_wayback_fallback(opts.wayback);
+    conf.set_answer_must_contain(opts.must_contain.clone());
+    conf.set_edited_after(opts.edited_after);
+    conf.set_blocked_hosts(opts.block_host.clone());
+    conf.set_explain_ranking(opts.explain_ranking);
+    conf.set_fit_to_terminal(opts.fit);
+    conf.set_max_answer_lines(opts.max_lines);
+    conf.set_max_answer_chars(opts.max_chars);
+    conf.set_accept_consent(!opts.no_consent);
+    conf.set_show_header(!opts.no_header);
+    conf.set_show_reputation(opts.reputation);
+    conf.set_show_question_meta(opts.meta);
+    conf.set_show_score(opts.score);
+    conf.set_show_comments(opts.comments);
+    conf.set_min_score(opts.min_score);
+    conf.set_fast_path(opts.fast);
+    conf.set_dry_run(opts.dry_run);
+    if let Some(separator) = &opts.separator {
+        conf.set_splitter(&separator.replace("\\n", "\n"));
+    }
+    conf.set_show_related(opts.related);
+    conf.set_confidence_guard(opts.confidence_guard);
+    conf.set_formatter_cmd(opts.formatter.clone());
+    conf.set_se_api_key(opts.api_key.clone());
+    conf.set_prefer_accepted(opts.accepted);
+    conf.set_answers_per_question(opts.answers_per_question);
+    if !opts.site.is_empty() {
+        conf.set_sites(opts.site.clone());
+    }
+    if opts.prefer_lang.is_some() {
+        conf.set_rank(Rank::LanguageThenVotes);
+        conf.set_language(opts.prefer_lang.clone());
+    }
+    conf.set_max_retries(opts.retries);
+    if opts.timeout == 0 {
+        conf.set_timeout(None);
+    } else {
+        conf.set_timeout(Some(Duration::from_secs(opts.timeout)));
+    }
+    conf.set_query_cache(opts.cache);
+    conf.set_refresh_query_cache(opts.refresh);
+    conf.set_highlight_language(opts.highlight.clone());
+    match &opts.color_depth {
+        Some(depth) => conf.set_color_depth(ColorDepth::from_str(depth)?),
+        None => conf.set_color_depth(ColorDepth::detect()),
+    }
+    conf.set_proxy(opts.proxy.clone())?;
+    conf.set_theme(opts.theme.clone())?;
+    if let Some(path) = &opts.theme_file {
+        conf.set_theme_file(path)?;
+    }
+    if let Some(dir) = &opts.syntax_dir {
+        conf.set_syntax_dir(dir, opts.replace_syntaxes)?;
+    }
+    Ok(conf)
 }
@@ -0,0 +1,293 @@
+//! Cache the final answers on disk, keyed by query.
+//!
+//! Asking the same question twice in a short time doesn't need to touch
+//! search engine or stackoverflow again, which is slow and may get us rate
+//! limited.  The cache is opt-in, see `Config::set_query_cache`.
+use crate::answer::{api_answers_with_client, postprocess_async, raw_answers_with_client};
+use crate::config::{Config, SearchEngine};
+use crate::engine::{dry_run_with_client, search_links_with_client};
+use crate::error::Result;
+use crate::paths;
+use crate::utils::client_builder;
+use reqwest::Client;
+use std::fs::{self, create_dir_all};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Answers cache which saves each answer into a file under the directory.
+pub struct QueryCache {
+    dir: PathBuf,
+}
+
+impl QueryCache {
+    /// Open the cache under hors cache directory, e.g: `$XDG_CACHE_HOME/hors/queries`.
+    ///
+    /// # Returns
+    ///
+    /// Return None if the cache directory can't be found.
+    pub fn open() -> Option<QueryCache> {
+        paths::cache_dir().map(|dir| QueryCache::at(dir.join("queries")))
+    }
+
+    /// Open the cache under the given directory.
+    pub fn at(dir: PathBuf) -> QueryCache {
+        QueryCache { dir }
+    }
+
+    /// Get cached answers by key.
+    ///
+    /// # Returns
+    ///
+    /// Return the answers if it's cached and not older than `ttl`, else None.
+    pub fn get(&self, key: &str, ttl: Duration) -> Option<String> {
+        let path: PathBuf = self.dir.join(key);
+        let modified: SystemTime = fs::metadata(&path).ok()?.modified().ok()?;
+        let age: Duration = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age > ttl {
+            debug!("Cached answers {} is too old: {:?}", key, age);
+            return None;
+        }
+        fs::read_to_string(path).ok()
+    }
+
+    /// Put answers into cache, the old answers of the key are replaced.
+    pub fn put(&self, key: &str, answers: &str) -> Result<()> {
+        create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(key), answers)?;
+        Ok(())
+    }
+}
+
+/// Make cache key from the query and the config which affects output.
+///
+/// The key is hashed by FNV-1a, so it's the same across Rust versions.
+pub fn cache_key(query: &str, search_engine: SearchEngine, conf: &Config) -> String {
+    let mut key: Vec<u8> = query.trim().to_lowercase().into_bytes();
+    key.push(b'|');
+    key.extend(format!("{:?}", search_engine).bytes());
+    key.push(b'|');
+    key.extend(
+        serde_json::to_vec(conf).expect(
+            "Config should be serialized :(  If you see this message, please fire an issue.",
+        ),
+    );
+    format!("{:016x}", fnv1a(&key))
+}
+
+/// 64-bit FNV-1a hash, see http://www.isthe.com/chongo/tech/comp/fnv/
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Search the query and get answers, through the cache if it's enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{self, Config, OutputOption, SearchEngine};
+///
+/// # async fn run() {
+/// let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+/// conf.set_query_cache(true);
+/// let answers: String = hors::search_answers("parse json in rust", SearchEngine::Bing, conf)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+///
+/// # Returns
+///
+/// The answers which can be print to terminal directly, or an Error.
+pub async fn search_answers(
+    query: &str,
+    search_engine: SearchEngine,
+    conf: Config,
+) -> Result<String> {
+    let client: Client = client_builder(&conf)?.build()?;
+    search_answers_with_client(query, search_engine, conf, &client).await
+}
+
+/// Search the query and get answers through the given client, see
+/// `search_answers` for details.
+///
+/// With `Config::dry_run`, the report of how the query is searched is
+/// returned instead, and the cache isn't touched.  Answers are cached before
+/// they're post-processed, e.g: fit to terminal.
+pub async fn search_answers_with_client(
+    query: &str,
+    search_engine: SearchEngine,
+    conf: Config,
+    client: &Client,
+) -> Result<String> {
+    if conf.dry_run() {
+        return dry_run_with_client(query, search_engine, &conf, client).await;
+    }
+    let answers: String = match QueryCache::open() {
+        Some(cache) if conf.query_cache() => {
+            search_answers_in(&cache, query, search_engine, &conf, client).await?
+        }
+        _ => search_and_answer(query, search_engine, &conf, client).await?,
+    };
+    Ok(postprocess_async(&conf, answers).await)
+}
+
+async fn search_answers_in(
+    cache: &QueryCache,
+    query: &str,
+    search_engine: SearchEngine,
+    conf: &Config,
+    client: &Client,
+) -> Result<String> {
+    let key: String = cache_key(query, search_engine, conf);
+    if !conf.refresh_query_cache() {
+        if let Some(answers) = cache.get(&key, conf.query_cache_ttl()) {
+            debug!("Get answers of {:?} from cache {}", query, key);
+            return Ok(answers);
+        }
+    }
+    let answers: String = search_and_answer(query, search_engine, conf, client).await?;
+    if let Err(err) = cache.put(&key, &answers) {
+        warn!("Can't save answers into cache, error msg: {:?}", err);
+    }
+    Ok(answers)
+}
+
+async fn search_and_answer(
+    query: &str,
+    search_engine: SearchEngine,
+    conf: &Config,
+    client: &Client,
+) -> Result<String> {
+    let links: Vec<String> = search_links_with_client(query, search_engine, conf, client).await?;
+    match search_engine {
+        // the api gives answers of all links at once.
+        SearchEngine::StackExchange => api_answers_with_client(&links, conf, client).await,
+        _ => raw_answers_with_client(&links, conf, client).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputOption;
+    use crate::test_utils::{MockResponse, MockServer};
+    use reqwest::{ClientBuilder, Proxy};
+
+    fn temp_cache(name: &str) -> QueryCache {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("hors-queries-{}-{}", name, std::process::id()));
+        QueryCache::at(dir)
+    }
+
+    /// Client which sends all requests to the mock server.
+    fn proxied_client(server: &MockServer) -> Client {
+        ClientBuilder::new()
+            .proxy(Proxy::all(&server.url("")).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_query_cache_get_and_put() {
+        let cache: QueryCache = temp_cache("get-put");
+        assert!(cache.get("key", Duration::from_secs(60)).is_none());
+        cache.put("key", "answers").unwrap();
+        assert_eq!(
+            cache.get("key", Duration::from_secs(60)),
+            Some(String::from("answers"))
+        );
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.get("key", Duration::from_millis(10)).is_none());
+        fs::remove_dir_all(cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let key: String = cache_key("parse json", SearchEngine::Bing, &conf);
+        assert_eq!(key, cache_key(" Parse JSON ", SearchEngine::Bing, &conf));
+        assert_ne!(key, cache_key("parse json", SearchEngine::Google, &conf));
+        assert_ne!(key, cache_key("parse yaml", SearchEngine::Bing, &conf));
+        let conf: Config = Config::new(OutputOption::All, 1, false);
+        assert_ne!(key, cache_key("parse json", SearchEngine::Bing, &conf));
+
+        // each output-affecting option is in the key.
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let mut numbered: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        numbered.set_code_line_numbers(true);
+        assert_ne!(key, cache_key("parse json", SearchEngine::Bing, &numbered));
+        let mut blocked: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        blocked.set_blocked_hosts(vec![String::from("example.com")]);
+        assert_ne!(key, cache_key("parse json", SearchEngine::Bing, &blocked));
+        // while post-processing and network options are not.
+        let mut fit: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        fit.set_fit_to_terminal(true);
+        fit.set_max_retries(5);
+        assert_eq!(
+            cache_key("parse json", SearchEngine::Bing, &conf),
+            cache_key("parse json", SearchEngine::Bing, &fit)
+        );
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[tokio::test]
+    async fn test_search_answers_when_cache_is_warm() {
+        let server = MockServer::start(vec![MockResponse::new(500, "unexpected")]);
+        let cache: QueryCache = temp_cache("warm");
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_query_cache(true);
+        let key: String = cache_key("parse json", SearchEngine::Bing, &conf);
+        cache.put(&key, "cached answers").unwrap();
+
+        let answers: String = search_answers_in(
+            &cache,
+            "parse json",
+            SearchEngine::Bing,
+            &conf,
+            &proxied_client(&server),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(answers, "cached answers");
+        assert_eq!(server.hits(), 0);
+        fs::remove_dir_all(cache.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_answers_when_cache_is_refreshed() {
+        let server = MockServer::start(vec![MockResponse::new(500, "unexpected")]);
+        let cache: QueryCache = temp_cache("refresh");
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_query_cache(true);
+        conf.set_refresh_query_cache(true);
+        let key: String = cache_key("parse json", SearchEngine::Bing, &conf);
+        cache.put(&key, "cached answers").unwrap();
+
+        let answers: Result<String> = search_answers_in(
+            &cache,
+            "parse json",
+            SearchEngine::Bing,
+            &conf,
+            &proxied_client(&server),
+        )
+        .await;
+
+        // the cache is bypassed, so the search goes to network.
+        assert!(answers.is_err());
+        assert!(server.hits() > 0);
+        fs::remove_dir_all(cache.dir).unwrap();
+    }
+}
@@ -1,7 +1,15 @@
+use crate::answer::SPLITTER;
 use crate::error::{Error, Result};
+use reqwest::Proxy;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxSet, SyntaxSetBuilder};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 /// The results output options.
 pub enum OutputOption {
     /// Only output links.
@@ -10,9 +18,73 @@ pub enum OutputOption {
     All,
     /// Only output code in answer.
     OnlyCode,
+    /// Output answer details as a json array, without ANSI escapes.
+    Json,
+    /// Output answer details as markdown, without ANSI escapes.
+    Markdown,
 }
 
-#[derive(Debug)]
+/// Timeout of each request under fast path.
+pub const FAST_PATH_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// The syntect theme which is used to colorize code by default.
+pub const DEFAULT_THEME: &str = "base16-eighties.dark";
+
+/// The StackExchange site which is searched by default.
+pub const DEFAULT_SITE: &str = "stackoverflow.com";
+
+/// Timeout of each request by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the cached answers are valid by default.
+pub const QUERY_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, PartialEq, Serialize)]
+/// How answers are ranked.
+pub enum Rank {
+    /// By votes, blended with recency if recency weight is given.
+    Votes,
+    /// Answers written in user's language go first, then by votes.
+    LanguageThenVotes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// How many colors the terminal supports.
+pub enum ColorDepth {
+    /// 24-bit colors.
+    TrueColor,
+    /// The xterm 256 colors palette.
+    Ansi256,
+}
+
+impl ColorDepth {
+    /// Detect color depth of current terminal by `COLORTERM` environment variable,
+    /// which is set to `truecolor` or `24bit` by terminals support truecolor.
+    pub fn detect() -> ColorDepth {
+        match std::env::var("COLORTERM") {
+            Ok(colorterm) if colorterm == "truecolor" || colorterm == "24bit" => {
+                ColorDepth::TrueColor
+            }
+            _ => ColorDepth::Ansi256,
+        }
+    }
+}
+
+/// Detect if the output should be colorized, which is false when the
+/// `NO_COLOR` environment variable is set, or stdout is not a terminal, e.g:
+/// the output is piped into a file.
+///
+/// See https://no-color.org for details of `NO_COLOR`.
+pub fn detect_colorize() -> bool {
+    colorize_by(std::env::var_os("NO_COLOR"), atty::is(atty::Stream::Stdout))
+}
+
+fn colorize_by(no_color: Option<OsString>, is_tty: bool) -> bool {
+    let no_color: bool = no_color.is_some_and(|value| !value.is_empty());
+    is_tty && !no_color
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
 /// supported search engine definition.
 pub enum SearchEngine {
     /// Microsoft bing search engine.
@@ -21,10 +93,15 @@ pub enum SearchEngine {
     Google,
     /// DuckDuckGo search engine.
     DuckDuckGo,
+    /// The official Stack Exchange API.
+    StackExchange,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 /// The user config information is integrated here.
+///
+/// The serialized config is a part of answers cache key, so fields which
+/// don't change the answers are skipped, e.g: network settings.
 pub struct Config {
     /// Terminal output options.
     option: OutputOption,
@@ -32,6 +109,125 @@ pub struct Config {
     numbers: u8,
     /// Indicate that the output code shoule be colorized or not.
     colorize: bool,
+    /// Output the first paragraph of answer when `OnlyCode` can't find any code.
+    code_mode_prose_fallback: bool,
+    /// Prefix each line of output code with it's line number.
+    code_line_numbers: bool,
+    /// Make search engine only matches the query in question title.
+    match_in_title: bool,
+    /// Interface language of search results, like `en`.
+    search_locale: Option<String>,
+    /// Country which search results are biased toward, like `us`.
+    search_region: Option<String>,
+    /// Directory to save fetched question pages.
+    #[serde(skip)]
+    save_html_dir: Option<PathBuf>,
+    /// Get question page from Wayback Machine if stackoverflow is unavailable.
+    #[serde(skip)]
+    wayback_fallback: bool,
+    /// Escape control characters in answer.
+    sanitize_control_chars: bool,
+    /// Only select answer which contains the keyword.
+    answer_must_contain: Option<String>,
+    /// How much the votes count matters when selecting answer.
+    vote_weight: f32,
+    /// How much the recency matters when selecting answer.
+    recency_weight: f32,
+    /// Prefer answers which are edited in or after the year.
+    edited_after: Option<u16>,
+    /// Hosts which should be removed from search results.
+    blocked_hosts: Vec<String>,
+    /// Only parse the part of question page which contains answers.
+    bounded_parse: bool,
+    /// How answers are ranked.
+    rank: Rank,
+    /// The programming language user prefers.
+    language: Option<String>,
+    /// Explain how answers are ranked in the output.
+    explain_ranking: bool,
+    /// Truncate the output to the height of terminal.
+    #[serde(skip)]
+    fit_to_terminal: bool,
+    /// Truncate each answer to the number of lines.
+    max_answer_lines: Option<usize>,
+    /// Truncate each answer to the number of characters.
+    max_answer_chars: Option<usize>,
+    /// Accept stackoverflow's cookie consent automatically.
+    accept_consent: bool,
+    /// Output reputation of answer's author.
+    show_reputation: bool,
+    /// Output tags and ask date of the question in the title line.
+    show_question_meta: bool,
+    /// Output score of the answer, and if it's accepted.
+    show_score: bool,
+    /// Output the `- Answer from <link>` header line of each answer.
+    show_header: bool,
+    /// Output top comments of the answer.
+    show_comments: bool,
+    /// Answers which score below it are skipped.
+    min_score: i16,
+    /// StackExchange sites to search within.
+    sites: Vec<String>,
+    /// Optimize purely for latency.
+    fast_path: bool,
+    /// Only report how the query is searched, without fetching answers.
+    #[serde(skip)]
+    dry_run: bool,
+    /// Separator between answers of questions, default to `SPLITTER`.
+    splitter: Option<String>,
+    /// List linked and related questions of the top question.
+    show_related: bool,
+    /// Return error when search results seem unrelated to the query.
+    confidence_guard: bool,
+    /// External command which the output is piped through.
+    #[serde(skip)]
+    formatter_cmd: Option<String>,
+    /// Key of Stack Exchange API.
+    #[serde(skip)]
+    se_api_key: Option<String>,
+    /// Filter of Stack Exchange API.
+    se_filter: Option<String>,
+    /// Select the accepted answer before others.
+    prefer_accepted: bool,
+    /// The number of answers to be output for each question.
+    answers_per_question: u8,
+    /// Language to highlight code, which overrides the one guessed from tags.
+    highlight_language: Option<String>,
+    /// The syntect theme to colorize code.
+    theme: Option<String>,
+    /// The `.tmTheme` file to colorize code.
+    theme_file: Option<PathBuf>,
+    /// Theme loaded from `theme_file`, it's used rather than `theme`.
+    #[serde(skip)]
+    custom_theme: Option<Theme>,
+    /// How many colors are used to colorize code.
+    color_depth: ColorDepth,
+    /// Cache answers on disk by query.
+    #[serde(skip)]
+    query_cache: bool,
+    /// How long the cached answers are valid.
+    #[serde(skip)]
+    query_cache_ttl: Duration,
+    /// Ignore the cached answers and search again.
+    #[serde(skip)]
+    refresh_query_cache: bool,
+    /// How many times a request is retried on transient failures.
+    #[serde(skip)]
+    max_retries: u32,
+    /// Delay before the first retry, it's doubled for each retry.
+    #[serde(skip)]
+    retry_base_delay: Duration,
+    /// Proxy which all requests go through.
+    #[serde(skip)]
+    proxy: Option<String>,
+    /// Timeout of each request.
+    #[serde(skip)]
+    timeout: Option<Duration>,
+    /// Directory of the custom syntaxes.
+    syntax_dir: Option<PathBuf>,
+    /// Syntaxes to colorize code, None means the default syntaxes of syntect.
+    #[serde(skip)]
+    syntax_set: Option<SyntaxSet>,
 }
 
 impl Config {
@@ -40,6 +236,58 @@ impl Config {
             option: output_option,
             numbers,
             colorize,
+            code_mode_prose_fallback: false,
+            code_line_numbers: false,
+            match_in_title: false,
+            search_locale: None,
+            search_region: None,
+            save_html_dir: None,
+            wayback_fallback: false,
+            sanitize_control_chars: false,
+            answer_must_contain: None,
+            vote_weight: 1.0,
+            recency_weight: 0.0,
+            edited_after: None,
+            blocked_hosts: vec![],
+            bounded_parse: false,
+            rank: Rank::Votes,
+            language: None,
+            explain_ranking: false,
+            fit_to_terminal: false,
+            max_answer_lines: None,
+            max_answer_chars: None,
+            accept_consent: true,
+            show_reputation: false,
+            show_question_meta: false,
+            show_score: false,
+            show_header: true,
+            show_comments: false,
+            min_score: 0,
+            sites: vec![String::from(DEFAULT_SITE)],
+            fast_path: false,
+            dry_run: false,
+            splitter: None,
+            show_related: false,
+            confidence_guard: false,
+            formatter_cmd: None,
+            se_api_key: None,
+            se_filter: None,
+            prefer_accepted: false,
+            answers_per_question: 1,
+            highlight_language: None,
+            theme: None,
+            color_depth: ColorDepth::TrueColor,
+            query_cache: false,
+            query_cache_ttl: QUERY_CACHE_TTL,
+            refresh_query_cache: false,
+            max_retries: 2,
+            retry_base_delay: Duration::from_millis(500),
+            proxy: None,
+            timeout: Some(DEFAULT_TIMEOUT),
+            syntax_dir: None,
+            syntax_set: None,
+            theme_file: None,
+            custom_theme: None,
         }
     }
 
@@ -47,12 +295,756 @@ impl Config {
         &self.option
     }
 
+    /// Number of answers to return, it's always 1 under fast path.
     pub fn numbers(&self) -> u8 {
-        self.numbers
+        if self.fast_path {
+            1
+        } else {
+            self.numbers
+        }
     }
 
+    /// Whether the code should be colorized, it's always false for json and
+    /// markdown output.
     pub fn colorize(&self) -> bool {
-        self.colorize
+        self.colorize && !matches!(self.option, OutputOption::Json | OutputOption::Markdown)
+    }
+
+    pub fn code_mode_prose_fallback(&self) -> bool {
+        self.code_mode_prose_fallback
+    }
+
+    /// When enabled, `OutputOption::OnlyCode` returns the first paragraph of
+    /// prose for answers which don't contain any code.
+    pub fn set_code_mode_prose_fallback(&mut self, fallback: bool) {
+        self.code_mode_prose_fallback = fallback;
+    }
+
+    pub fn code_line_numbers(&self) -> bool {
+        self.code_line_numbers
+    }
+
+    /// When enabled, `OutputOption::OnlyCode` prefixes each code line with
+    /// a right-aligned line number.
+    pub fn set_code_line_numbers(&mut self, line_numbers: bool) {
+        self.code_line_numbers = line_numbers;
+    }
+
+    pub fn match_in_title(&self) -> bool {
+        self.match_in_title
+    }
+
+    /// When enabled, search engine uses title operator (e.g: `intitle:`), so
+    /// the results are biased toward questions whose title contains the query.
+    pub fn set_match_in_title(&mut self, match_in_title: bool) {
+        self.match_in_title = match_in_title;
+    }
+
+    pub fn search_locale(&self) -> Option<&str> {
+        self.search_locale.as_deref()
+    }
+
+    /// Set the language of search results (google's `hl` parameter), e.g:
+    /// `en`, default to None which lets search engine decide.
+    pub fn set_search_locale(&mut self, locale: Option<String>) {
+        self.search_locale = locale;
+    }
+
+    pub fn search_region(&self) -> Option<&str> {
+        self.search_region.as_deref()
+    }
+
+    /// Set the region of search results (google's `gl` parameter), e.g: `us`,
+    /// default to None which lets search engine decide.
+    pub fn set_search_region(&mut self, region: Option<String>) {
+        self.search_region = region;
+    }
+
+    pub fn save_html_dir(&self) -> Option<&Path> {
+        self.save_html_dir.as_deref()
+    }
+
+    /// When set, each fetched question page is saved into the directory as
+    /// `<question id>.html`.
+    pub fn set_save_html_dir(&mut self, dir: Option<PathBuf>) {
+        self.save_html_dir = dir;
+    }
+
+    pub fn wayback_fallback(&self) -> bool {
+        self.wayback_fallback
+    }
+
+    /// When enabled, question page is fetched from Wayback Machine if the live
+    /// page can't be fetched.  Note that the archived page may be out of date.
+    pub fn set_wayback_fallback(&mut self, fallback: bool) {
+        self.wayback_fallback = fallback;
+    }
+
+    pub fn sanitize_control_chars(&self) -> bool {
+        self.sanitize_control_chars
+    }
+
+    /// When enabled, control characters (except tab and newline) in answer
+    /// text and code are escaped, so they can't mess up terminal.
+    pub fn set_sanitize_control_chars(&mut self, sanitize: bool) {
+        self.sanitize_control_chars = sanitize;
+    }
+
+    pub fn answer_must_contain(&self) -> Option<&str> {
+        self.answer_must_contain.as_deref()
+    }
+
+    /// When set, only answers which contain the keyword (ignore case) are selected.
+    pub fn set_answer_must_contain(&mut self, keyword: Option<String>) {
+        self.answer_must_contain = keyword;
+    }
+
+    pub fn vote_weight(&self) -> f32 {
+        self.vote_weight
+    }
+
+    /// Set the weight of votes when selecting answer, default to 1.0.
+    pub fn set_vote_weight(&mut self, weight: f32) {
+        self.vote_weight = weight;
+    }
+
+    pub fn recency_weight(&self) -> f32 {
+        self.recency_weight
+    }
+
+    /// Set the weight of recency when selecting answer, default to 0.0, which
+    /// means answers are selected by votes only.
+    pub fn set_recency_weight(&mut self, weight: f32) {
+        self.recency_weight = weight;
+    }
+
+    pub fn edited_after(&self) -> Option<u16> {
+        self.edited_after
+    }
+
+    /// Set the year which answers should be edited in or after, older answers
+    /// are skipped unless none of answers is new enough, default to None.
+    pub fn set_edited_after(&mut self, year: Option<u16>) {
+        self.edited_after = year;
+    }
+
+    pub fn blocked_hosts(&self) -> &[String] {
+        &self.blocked_hosts
+    }
+
+    /// Set hosts which should be removed from search results, the subdomains
+    /// of these hosts are removed too.
+    pub fn set_blocked_hosts(&mut self, hosts: Vec<String>) {
+        self.blocked_hosts = hosts;
+    }
+
+    pub fn bounded_parse(&self) -> bool {
+        self.bounded_parse
+    }
+
+    /// When enabled, sidebar, footer and scripts of question page are removed
+    /// before parsing, which saves memory for huge pages.
+    pub fn set_bounded_parse(&mut self, bounded: bool) {
+        self.bounded_parse = bounded;
+    }
+
+    pub fn rank(&self) -> &Rank {
+        &self.rank
+    }
+
+    /// Set how answers are ranked, default to `Rank::Votes`.
+    pub fn set_rank(&mut self, rank: Rank) {
+        self.rank = rank;
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Set the programming language user prefers, it's a token like `rust`
+    /// or `py`, which can be found in stackoverflow's `lang-xxx` class.
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.language = language;
+    }
+
+    pub fn explain_ranking(&self) -> bool {
+        self.explain_ranking
+    }
+
+    /// Set if the score of each answer and why it was chosen are output
+    /// after the answer.
+    pub fn set_explain_ranking(&mut self, explain: bool) {
+        self.explain_ranking = explain;
+    }
+
+    pub fn fit_to_terminal(&self) -> bool {
+        self.fit_to_terminal
+    }
+
+    /// Set if the output is truncated to roughly the terminal height, with
+    /// a footer which tells how many lines are left.
+    pub fn set_fit_to_terminal(&mut self, fit: bool) {
+        self.fit_to_terminal = fit;
+    }
+
+    pub fn max_answer_lines(&self) -> Option<usize> {
+        self.max_answer_lines
+    }
+
+    /// Set how many lines of each answer are output at most, the truncated
+    /// answer ends with a marker which points to the link.  Default to None,
+    /// which means answers are not truncated.
+    pub fn set_max_answer_lines(&mut self, lines: Option<usize>) {
+        self.max_answer_lines = lines;
+    }
+
+    pub fn max_answer_chars(&self) -> Option<usize> {
+        self.max_answer_chars
+    }
+
+    /// Set how many characters of each answer are output at most, ANSI
+    /// escapes of colorized code are not counted.  Default to None.
+    pub fn set_max_answer_chars(&mut self, chars: Option<usize>) {
+        self.max_answer_chars = chars;
+    }
+
+    pub fn accept_consent(&self) -> bool {
+        self.accept_consent
+    }
+
+    /// Set if hors accepts the cookie consent when stackoverflow asks for it
+    /// (for EU users), default to true.  If it's false, an error is returned
+    /// instead.
+    pub fn set_accept_consent(&mut self, accept: bool) {
+        self.accept_consent = accept;
+    }
+
+    pub fn show_reputation(&self) -> bool {
+        self.show_reputation
+    }
+
+    /// Set if the author and their reputation are output before the answer, so
+    /// user can know how trustworthy the answer is.
+    pub fn set_show_reputation(&mut self, show: bool) {
+        self.show_reputation = show;
+    }
+
+    pub fn show_question_meta(&self) -> bool {
+        self.show_question_meta
+    }
+
+    /// Set if tags and ask date of the question are output in the title line,
+    /// so user can judge relevance and staleness of the answer.
+    pub fn set_show_question_meta(&mut self, show: bool) {
+        self.show_question_meta = show;
+    }
+
+    pub fn show_score(&self) -> bool {
+        self.show_score
+    }
+
+    /// Set if a header like `[score: 142] [accepted]` is output before the
+    /// answer details, it's not output when only code is output.
+    pub fn set_show_score(&mut self, show: bool) {
+        self.show_score = show;
+    }
+
+    pub fn show_header(&self) -> bool {
+        self.show_header
+    }
+
+    /// Set if the `- Answer from <link>` line is output before each answer,
+    /// default to true.  When it's false, the score header and question meta
+    /// are not output either, so only the answer is left.
+    pub fn set_show_header(&mut self, show: bool) {
+        self.show_header = show;
+    }
+
+    pub fn show_comments(&self) -> bool {
+        self.show_comments
+    }
+
+    /// Set if the top comments are output under the answer details, they
+    /// sometimes correct the answer.  It only works for `OutputOption::All`.
+    pub fn set_show_comments(&mut self, show: bool) {
+        self.show_comments = show;
+    }
+
+    pub fn min_score(&self) -> i16 {
+        self.min_score
+    }
+
+    /// Set the minimum score of answers, answers which score below it are
+    /// skipped, default to 0 so only the down voted answers are skipped.
+    pub fn set_min_score(&mut self, score: i16) {
+        self.min_score = score;
+    }
+
+    pub fn sites(&self) -> &[String] {
+        &self.sites
+    }
+
+    /// Set StackExchange sites to search within, like `serverfault.com` or
+    /// `superuser.com`, links from all sites are merged, and their answers are
+    /// ranked by votes of the best answer.  Default to `stackoverflow.com` only.
+    pub fn set_sites(&mut self, sites: Vec<String>) {
+        self.sites = sites;
+    }
+
+    pub fn fast_path(&self) -> bool {
+        self.fast_path
+    }
+
+    /// Set if hors optimizes purely for latency, which is useful for
+    /// interactive callers like editor completions.
+    ///
+    /// Under fast path only the top link is used, each request times out
+    /// after `FAST_PATH_TIMEOUT`, and nothing is retried, so http fallback of
+    /// search, Wayback Machine fallback, cookie consent and refetching stale
+    /// cache are all skipped.  Cache is still read but never written.  The
+    /// tradeoff is that hors fails more often, and the answer may come from
+    /// a stale page.
+    pub fn set_fast_path(&mut self, fast: bool) {
+        self.fast_path = fast;
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Set if hors only reports the query url and the extracted links of each
+    /// site, and no answer page is fetched.  It's useful to find out which
+    /// step breaks when the search gets nothing.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    pub fn splitter(&self) -> &str {
+        self.splitter.as_deref().unwrap_or(SPLITTER)
+    }
+
+    /// Set the separator between answers of questions (or links under
+    /// `OutputOption::Links`), e.g: `"\n---\n"` for scripts which split the
+    /// output, default to `SPLITTER`.
+    pub fn set_splitter(&mut self, splitter: &str) {
+        self.splitter = Some(splitter.to_string());
+    }
+
+    pub fn show_related(&self) -> bool {
+        self.show_related
+    }
+
+    /// Set if "Linked" and "Related" questions in the sidebar of the top
+    /// question are listed after its answer.
+    pub fn set_show_related(&mut self, show: bool) {
+        self.show_related = show;
+    }
+
+    pub fn confidence_guard(&self) -> bool {
+        self.confidence_guard
+    }
+
+    /// Set if searching returns `Error::LowConfidence` when the top links
+    /// hardly share any word with the query, so user can refine the query.
+    pub fn set_confidence_guard(&mut self, guard: bool) {
+        self.confidence_guard = guard;
+    }
+
+    pub fn formatter_cmd(&self) -> Option<&str> {
+        self.formatter_cmd.as_deref()
+    }
+
+    /// Set the command which the output is piped through, like `bat -l md`,
+    /// and its stdout is returned instead.  The raw output is returned if
+    /// the command fails.
+    pub fn set_formatter_cmd(&mut self, command: Option<String>) {
+        self.formatter_cmd = command;
+    }
+
+    pub fn se_api_key(&self) -> Option<&str> {
+        self.se_api_key.as_deref()
+    }
+
+    /// Set the key of Stack Exchange API, which raises the daily quota.
+    pub fn set_se_api_key(&mut self, key: Option<String>) {
+        self.se_api_key = key;
+    }
+
+    pub fn se_filter(&self) -> Option<&str> {
+        self.se_filter.as_deref()
+    }
+
+    /// Set the filter id of Stack Exchange API, which controls the fields
+    /// returned by the api.  Default to `withbody`.
+    pub fn set_se_filter(&mut self, filter: Option<String>) {
+        self.se_filter = filter;
+    }
+
+    pub fn prefer_accepted(&self) -> bool {
+        self.prefer_accepted
+    }
+
+    /// Set if the answer accepted by the asker is selected before others,
+    /// answers are still ranked by votes if no answer is accepted.
+    pub fn set_prefer_accepted(&mut self, prefer: bool) {
+        self.prefer_accepted = prefer;
+    }
+
+    pub fn answers_per_question(&self) -> u8 {
+        self.answers_per_question
+    }
+
+    /// Set how many top ranked answers are output for each question, default
+    /// to 1.
+    ///
+    /// It's independent of `numbers`, which is how many questions are consulted.
+    /// e.g: numbers 1 and answers per question 3 fetches one question page, and
+    /// outputs its top 3 answers.
+    pub fn set_answers_per_question(&mut self, answers: u8) {
+        self.answers_per_question = answers;
+    }
+
+    pub fn highlight_language(&self) -> Option<&str> {
+        self.highlight_language.as_deref()
+    }
+
+    /// Set the language to highlight code, like `rust` or `sh`, rather than
+    /// guessing it from question tags.  Aliases like `golang` are accepted
+    /// the same as tags, and code is not highlighted if the language is unknown.
+    pub fn set_highlight_language(&mut self, language: Option<String>) {
+        self.highlight_language = language;
+    }
+
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// Set the syntect theme to colorize code, like `InspiredGitHub` for light
+    /// terminals, default to `DEFAULT_THEME`.
+    ///
+    /// # Returns
+    ///
+    /// Error which lists available themes if the theme doesn't exist.
+    pub fn set_theme(&mut self, theme: Option<String>) -> Result<()> {
+        if let Some(name) = &theme {
+            let themes: Vec<String> = ThemeSet::load_defaults().themes.into_keys().collect();
+            if !themes.contains(name) {
+                return Err(Error::Config(format!(
+                    "Theme {} is not found, available themes: {}",
+                    name,
+                    themes.join(", ")
+                )));
+            }
+        }
+        self.theme = theme;
+        Ok(())
+    }
+
+    pub fn theme_file(&self) -> Option<&Path> {
+        self.theme_file.as_deref()
+    }
+
+    pub fn custom_theme(&self) -> Option<&Theme> {
+        self.custom_theme.as_ref()
+    }
+
+    /// Load the `.tmTheme` file to colorize code, it's used rather than the
+    /// theme set by `set_theme`.
+    ///
+    /// # Returns
+    ///
+    /// Error if the file doesn't exist, or it's not a valid theme.
+    pub fn set_theme_file(&mut self, path: &Path) -> Result<()> {
+        if !path.is_file() {
+            return Err(Error::Config(format!(
+                "Theme file {} is not found",
+                path.display()
+            )));
+        }
+        let theme: Theme = ThemeSet::get_theme(path).map_err(|err| {
+            Error::Config(format!("Can't load theme file {}: {}", path.display(), err))
+        })?;
+        self.theme_file = Some(path.to_path_buf());
+        self.custom_theme = Some(theme);
+        Ok(())
+    }
+
+    pub fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    /// Set how many colors are used to colorize code, default to truecolor.
+    /// Use `ColorDepth::detect` to get the depth of current terminal.
+    pub fn set_color_depth(&mut self, depth: ColorDepth) {
+        self.color_depth = depth;
+    }
+
+    pub fn query_cache(&self) -> bool {
+        self.query_cache
+    }
+
+    /// Set if answers are cached on disk by query, so asking the same
+    /// question again doesn't touch network, see `hors::search_answers`.
+    pub fn set_query_cache(&mut self, cache: bool) {
+        self.query_cache = cache;
+    }
+
+    pub fn query_cache_ttl(&self) -> Duration {
+        self.query_cache_ttl
+    }
+
+    /// Set how long the cached answers are valid, default to `QUERY_CACHE_TTL`.
+    pub fn set_query_cache_ttl(&mut self, ttl: Duration) {
+        self.query_cache_ttl = ttl;
+    }
+
+    pub fn refresh_query_cache(&self) -> bool {
+        self.refresh_query_cache
+    }
+
+    /// Set if the cached answers are ignored, the new answers are still put
+    /// into cache.
+    pub fn set_refresh_query_cache(&mut self, refresh: bool) {
+        self.refresh_query_cache = refresh;
+    }
+
+    /// How many times a request is retried, it's always 0 under fast path.
+    pub fn max_retries(&self) -> u32 {
+        if self.fast_path {
+            0
+        } else {
+            self.max_retries
+        }
+    }
+
+    /// Set how many times a request is retried when it times out, or the
+    /// server responds with 5xx or 429, default to 2.
+    pub fn set_max_retries(&mut self, retries: u32) {
+        self.max_retries = retries;
+    }
+
+    pub fn retry_base_delay(&self) -> Duration {
+        self.retry_base_delay
+    }
+
+    /// Set the delay before the first retry, default to 500 milliseconds.
+    pub fn set_retry_base_delay(&mut self, delay: Duration) {
+        self.retry_base_delay = delay;
+    }
+
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    /// Set the proxy which all requests go through, like `http://127.0.0.1:8080`
+    /// or `socks5://127.0.0.1:1080`.  When it's None, proxy is taken from
+    /// `HTTP_PROXY` and `HTTPS_PROXY` environment variables.
+    ///
+    /// # Returns
+    ///
+    /// Error if the proxy is not a valid http or socks5 url.
+    pub fn set_proxy(&mut self, proxy: Option<String>) -> Result<()> {
+        if let Some(proxy) = &proxy {
+            check_proxy(proxy)?;
+        }
+        self.proxy = proxy;
+        Ok(())
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Set timeout of each request, default to `DEFAULT_TIMEOUT`, None means
+    /// requests never time out.  It's overridden by `FAST_PATH_TIMEOUT` under
+    /// fast path.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn syntax_dir(&self) -> Option<&Path> {
+        self.syntax_dir.as_deref()
+    }
+
+    pub fn syntax_set(&self) -> Option<&SyntaxSet> {
+        self.syntax_set.as_ref()
+    }
+
+    /// Load `.sublime-syntax` files in the directory to colorize code, they are
+    /// merged with the default syntaxes unless `replace_defaults` is true.
+    ///
+    /// # Returns
+    ///
+    /// Error if the directory doesn't exist, or it has no valid syntax.
+    pub fn set_syntax_dir(&mut self, dir: &Path, replace_defaults: bool) -> Result<()> {
+        if !dir.is_dir() {
+            return Err(Error::Config(format!(
+                "Syntax directory {} is not found",
+                dir.display()
+            )));
+        }
+        let (mut builder, builtin): (SyntaxSetBuilder, usize) = if replace_defaults {
+            let mut builder: SyntaxSetBuilder = SyntaxSetBuilder::new();
+            // plain text is used when the language is unknown.
+            builder.add_plain_text_syntax();
+            (builder, 1)
+        } else {
+            let defaults: SyntaxSet = SyntaxSet::load_defaults_newlines();
+            let builtin: usize = defaults.syntaxes().len();
+            (defaults.into_builder(), builtin)
+        };
+        builder.add_from_folder(dir, true).map_err(|err| {
+            Error::Config(format!(
+                "Can't load syntaxes from {}: {}",
+                dir.display(),
+                err
+            ))
+        })?;
+        let syntax_set: SyntaxSet = builder.build();
+        if syntax_set.syntaxes().len() == builtin {
+            return Err(Error::Config(format!(
+                "No .sublime-syntax file is found in {}",
+                dir.display()
+            )));
+        }
+        self.syntax_dir = Some(dir.to_path_buf());
+        self.syntax_set = Some(syntax_set);
+        Ok(())
+    }
+}
+
+/// Build `Config` with fluent setters, the settings which are not given use
+/// the defaults of `Config::new(OutputOption::OnlyCode, 1, false)`.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{Config, ConfigBuilder, OutputOption};
+/// use std::time::Duration;
+///
+/// let conf: Config = ConfigBuilder::new()
+///     .option(OutputOption::All)
+///     .numbers(3)
+///     .colorize(true)
+///     .theme("InspiredGitHub")
+///     .timeout(Duration::from_secs(5))
+///     .build()
+///     .unwrap();
+/// assert_eq!(conf.numbers(), 3);
+/// assert_eq!(conf.theme(), Some("InspiredGitHub"));
+/// ```
+///
+/// Invalid settings are reported when building:
+///
+/// ```rust
+/// use hors::ConfigBuilder;
+///
+/// assert!(ConfigBuilder::new().theme("not-a-theme").build().is_err());
+/// ```
+#[derive(Debug)]
+pub struct ConfigBuilder {
+    conf: Config,
+    theme: Option<String>,
+    proxy: Option<String>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> ConfigBuilder {
+        ConfigBuilder {
+            conf: Config::new(OutputOption::OnlyCode, 1, false),
+            theme: None,
+            proxy: None,
+        }
+    }
+
+    pub fn option(mut self, option: OutputOption) -> ConfigBuilder {
+        self.conf.option = option;
+        self
+    }
+
+    pub fn numbers(mut self, numbers: u8) -> ConfigBuilder {
+        self.conf.numbers = numbers;
+        self
+    }
+
+    pub fn colorize(mut self, colorize: bool) -> ConfigBuilder {
+        self.conf.colorize = colorize;
+        self
+    }
+
+    /// See `Config::set_answers_per_question`.
+    pub fn answers_per_question(mut self, answers: u8) -> ConfigBuilder {
+        self.conf.set_answers_per_question(answers);
+        self
+    }
+
+    /// See `Config::set_sites`.
+    pub fn sites(mut self, sites: Vec<String>) -> ConfigBuilder {
+        self.conf.set_sites(sites);
+        self
+    }
+
+    /// See `Config::set_theme`, the theme is checked when building.
+    pub fn theme(mut self, theme: &str) -> ConfigBuilder {
+        self.theme = Some(theme.to_string());
+        self
+    }
+
+    /// See `Config::set_timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> ConfigBuilder {
+        self.conf.set_timeout(Some(timeout));
+        self
+    }
+
+    /// See `Config::set_proxy`, the proxy is checked when building.
+    pub fn proxy(mut self, proxy: &str) -> ConfigBuilder {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Build the config.
+    ///
+    /// # Returns
+    ///
+    /// Error if the theme or proxy is invalid.
+    pub fn build(self) -> Result<Config> {
+        let mut conf: Config = self.conf;
+        conf.set_theme(self.theme)?;
+        conf.set_proxy(self.proxy)?;
+        Ok(conf)
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+}
+
+/// Check if the proxy can be used by reqwest.
+pub(crate) fn check_proxy(proxy: &str) -> Result<Proxy> {
+    let scheme: &str = proxy.split("://").next().unwrap_or_default();
+    if !["http", "https", "socks5", "socks5h"].contains(&scheme) || !proxy.contains("://") {
+        return Err(Error::Config(format!(
+            "Proxy {} is not supported, it should start with http://, https:// or socks5://",
+            proxy
+        )));
+    }
+    Proxy::all(proxy).map_err(|err| Error::Config(format!("Proxy {} is invalid: {}", proxy, err)))
+}
+
+impl FromStr for ColorDepth {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+            "256" => Ok(ColorDepth::Ansi256),
+            _ => Err(Error::Config(format!(
+                "Color depth {} is not supported, it should be truecolor, 24bit or 256",
+                s
+            ))),
+        }
     }
 }
 
@@ -64,7 +1056,11 @@ impl FromStr for SearchEngine {
             "bing" => Ok(SearchEngine::Bing),
             "google" => Ok(SearchEngine::Google),
             "duckduckgo" => Ok(SearchEngine::DuckDuckGo),
-            _ => Err(Error::from_parse("Not supported search engine")),
+            "stackexchange" => Ok(SearchEngine::StackExchange),
+            _ => Err(Error::Config(format!(
+                "Search engine {} is not supported",
+                s
+            ))),
         }
     }
 }
@@ -76,14 +1072,191 @@ mod tests {
     #[test]
     fn test_search_engine_from_str() {
         let search_engine = SearchEngine::from_str("bing");
-        assert_eq!(search_engine.is_ok(), true);
+        assert!(search_engine.is_ok());
         let search_engine = SearchEngine::from_str("google");
-        assert_eq!(search_engine.is_ok(), true);
+        assert!(search_engine.is_ok());
+        let search_engine = SearchEngine::from_str("stackexchange");
+        assert!(search_engine.is_ok());
+    }
+
+    #[test]
+    fn test_set_theme() {
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert!(conf.set_theme(Some(String::from("InspiredGitHub"))).is_ok());
+        assert_eq!(conf.theme(), Some("InspiredGitHub"));
+
+        match conf.set_theme(Some(String::from("not-a-theme"))) {
+            Err(Error::Config(message)) => {
+                assert!(message.starts_with("Theme not-a-theme is not found"));
+                assert!(message.contains(DEFAULT_THEME));
+                assert!(message.contains("InspiredGitHub"));
+            }
+            other => panic!("Invalid theme error is expected, got {:?}", other),
+        }
+        assert_eq!(conf.theme(), Some("InspiredGitHub"));
+    }
+
+    const CUSTOM_SYNTAX: &str = r#"%YAML 1.2
+---
+name: HorsDSL
+file_extensions: [horsdsl]
+scope: source.horsdsl
+contexts:
+  main:
+    - match: '\bneigh\b'
+      scope: keyword.control.horsdsl
+"#;
+
+    fn syntax_dir(name: &str, syntax: Option<&str>) -> PathBuf {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("hors-syntaxes-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        if let Some(syntax) = syntax {
+            std::fs::write(dir.join("HorsDSL.sublime-syntax"), syntax).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_set_syntax_dir() {
+        let dir: PathBuf = syntax_dir("valid", Some(CUSTOM_SYNTAX));
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_syntax_dir(&dir, false).unwrap();
+        let syntax_set: &SyntaxSet = conf.syntax_set().unwrap();
+        assert!(syntax_set.find_syntax_by_token("horsdsl").is_some());
+        assert!(syntax_set.find_syntax_by_token("rust").is_some());
+        assert_eq!(conf.syntax_dir(), Some(dir.as_path()));
+
+        conf.set_syntax_dir(&dir, true).unwrap();
+        let syntax_set: &SyntaxSet = conf.syntax_set().unwrap();
+        assert!(syntax_set.find_syntax_by_token("horsdsl").is_some());
+        assert!(syntax_set.find_syntax_by_token("rust").is_none());
+        assert_eq!(syntax_set.find_syntax_plain_text().name, "Plain Text");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_set_syntax_dir_when_it_is_invalid() {
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let missing: PathBuf = std::env::temp_dir().join("hors-syntaxes-not-exist");
+        let empty: PathBuf = syntax_dir("empty", None);
+        let invalid: PathBuf = syntax_dir("invalid", Some("name: [broken"));
+
+        for (dir, reason) in &[
+            (&missing, "is not found"),
+            (&empty, "No .sublime-syntax file is found"),
+            (&invalid, "Can't load syntaxes from"),
+        ] {
+            match conf.set_syntax_dir(dir, false) {
+                Err(Error::Config(message)) => assert!(
+                    message.contains(reason),
+                    "{:?} should contain {:?}",
+                    message,
+                    reason
+                ),
+                other => panic!("Invalid syntax error is expected, got {:?}", other),
+            }
+        }
+        assert!(conf.syntax_set().is_none());
+        std::fs::remove_dir_all(empty).unwrap();
+        std::fs::remove_dir_all(invalid).unwrap();
+    }
+
+    #[test]
+    fn test_set_theme_file_when_it_is_invalid() {
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        let missing: PathBuf = std::env::temp_dir().join("hors-theme-not-exist.tmTheme");
+        match conf.set_theme_file(&missing) {
+            Err(Error::Config(message)) => assert!(message.ends_with("is not found")),
+            other => panic!("Invalid theme error is expected, got {:?}", other),
+        }
+
+        let invalid: PathBuf =
+            std::env::temp_dir().join(format!("hors-theme-{}.tmTheme", std::process::id()));
+        std::fs::write(&invalid, "<plist><dict><key>name").unwrap();
+        match conf.set_theme_file(&invalid) {
+            Err(Error::Config(message)) => {
+                assert!(message.starts_with("Can't load theme file"))
+            }
+            other => panic!("Invalid theme error is expected, got {:?}", other),
+        }
+        assert!(conf.custom_theme().is_none());
+        std::fs::remove_file(invalid).unwrap();
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let conf: Config = ConfigBuilder::new().build().unwrap();
+        assert!(matches!(conf.option(), OutputOption::OnlyCode));
+        assert_eq!(conf.numbers(), 1);
+        assert!(!conf.colorize());
+        assert_eq!(conf.timeout(), Some(DEFAULT_TIMEOUT));
+
+        let conf: Config = ConfigBuilder::new()
+            .option(OutputOption::Json)
+            .numbers(2)
+            .answers_per_question(3)
+            .sites(vec![String::from("superuser.com")])
+            .proxy("socks5://127.0.0.1:1080")
+            .build()
+            .unwrap();
+        assert!(matches!(conf.option(), OutputOption::Json));
+        assert_eq!(conf.numbers(), 2);
+        assert_eq!(conf.answers_per_question(), 3);
+        assert_eq!(conf.sites(), &[String::from("superuser.com")]);
+        assert_eq!(conf.proxy(), Some("socks5://127.0.0.1:1080"));
+
+        match ConfigBuilder::new().proxy("ftp://127.0.0.1").build() {
+            Err(Error::Config(_)) => {}
+            other => panic!("Invalid proxy error is expected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_set_proxy() {
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        assert!(conf
+            .set_proxy(Some(String::from("http://127.0.0.1:8080")))
+            .is_ok());
+        assert!(conf
+            .set_proxy(Some(String::from("socks5://127.0.0.1:1080")))
+            .is_ok());
+        assert_eq!(conf.proxy(), Some("socks5://127.0.0.1:1080"));
+
+        match conf.set_proxy(Some(String::from("ftp://127.0.0.1:21"))) {
+            Err(Error::Config(message)) => {
+                assert!(message.starts_with("Proxy ftp://127.0.0.1:21 is not supported"))
+            }
+            other => panic!("Invalid proxy error is expected, got {:?}", other),
+        }
+        assert!(conf
+            .set_proxy(Some(String::from("127.0.0.1:8080")))
+            .is_err());
+        assert_eq!(conf.proxy(), Some("socks5://127.0.0.1:1080"));
+    }
+
+    #[test]
+    fn test_color_depth_from_str() {
+        assert_eq!(
+            ColorDepth::from_str("truecolor").unwrap(),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(ColorDepth::from_str("256").unwrap(), ColorDepth::Ansi256);
+        assert!(matches!(ColorDepth::from_str("16"), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_colorize_by() {
+        assert!(colorize_by(None, true));
+        assert!(!colorize_by(None, false));
+        assert!(!colorize_by(Some(OsString::from("1")), true));
+        // empty `NO_COLOR` is ignored.
+        assert!(colorize_by(Some(OsString::new()), true));
     }
 
     #[test]
     fn test_search_engine_from_invalid_str() {
         let search_engine = SearchEngine::from_str("what's this?");
-        assert_eq!(search_engine.is_err(), true);
+        assert!(search_engine.is_err());
     }
 }
@@ -1,31 +1,28 @@
-use super::Engine;
+use super::{encode_query, Engine};
 use select::document::Document;
 use select::predicate::{Class, Name, Predicate};
 
 pub struct Bing;
 
 impl Engine for Bing {
-    fn get_query_url(&self, query: &str, use_https: bool) -> String {
+    fn get_query_url(&self, query: &str, site: &str, use_https: bool) -> String {
         if use_https {
             format!(
-                "https://www.bing.com/search?q=site:stackoverflow.com%20{}",
-                query
+                "https://www.bing.com/search?q={}",
+                encode_query(site, query)
             )
         } else {
-            format!(
-                "http://www.bing.com/search?q=site:stackoverflow.com%20{}",
-                query
-            )
+            format!("http://www.bing.com/search?q={}", encode_query(site, query))
         }
     }
 
-    fn extract_links(&self, page: &str) -> Option<Vec<String>> {
+    fn extract_links(&self, page: &str, site: &str) -> Option<Vec<String>> {
         let doc: Document = Document::from(page);
         let target_elements =
             doc.find(Class("b_algo").descendant(Name("h2")).descendant(Name("a")));
         let links: Vec<String> = target_elements
             .filter_map(|node| node.attr("href"))
-            .filter(|link| link.contains("stackoverflow.com"))
+            .filter(|link| link.contains(site))
             .map(String::from)
             .collect();
 
@@ -60,8 +57,8 @@ mod tests {
     </body>
 </html>"#,
         );
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_some(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_some());
         assert_eq!(
             possible_links.unwrap(),
             vec![
@@ -75,27 +72,51 @@ mod tests {
     fn test_extract_links_when_there_are_no_links_available() {
         let page: String = String::from("<html></html>");
         let engine = Bing;
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_none(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_none());
     }
 
     #[test]
     fn test_get_query_url() {
         let engine = Bing;
-        let result: String = engine.get_query_url(&String::from("how to write unit test"), true);
+        let result: String = engine.get_query_url(
+            &String::from("how to write unit test"),
+            "stackoverflow.com",
+            true,
+        );
         assert_eq!(
-            "https://www.bing.com/search?q=site:stackoverflow.com%20how to write unit test",
+            "https://www.bing.com/search?q=site%3Astackoverflow.com+how+to+write+unit+test",
             result
         );
     }
 
+    #[test]
+    fn test_match_in_title() {
+        let engine = Bing;
+        let result: String = engine.match_in_title("value moved here");
+        assert_eq!("intitle:value intitle:moved intitle:here", result);
+    }
+
     #[test]
     fn test_get_query_url_with_https_option_disabled() {
         let engine = Bing;
-        let result: String = engine.get_query_url(&String::from("how to write unit test"), false);
+        let result: String = engine.get_query_url(
+            &String::from("how to write unit test"),
+            "stackoverflow.com",
+            false,
+        );
         assert_eq!(
-            "http://www.bing.com/search?q=site:stackoverflow.com%20how to write unit test",
+            "http://www.bing.com/search?q=site%3Astackoverflow.com+how+to+write+unit+test",
             result
         );
     }
+
+    #[test]
+    fn test_get_query_url_of_other_site() {
+        let engine = Bing;
+        assert_eq!(
+            engine.get_query_url("ssh tunnel", "superuser.com", true),
+            "https://www.bing.com/search?q=site%3Asuperuser.com+ssh+tunnel"
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use super::Engine;
+use super::{encode_query, Engine};
 use select::document::Document;
 use select::predicate::Class;
 use url::form_urlencoded;
@@ -6,33 +6,32 @@ use url::form_urlencoded;
 pub struct DuckDuckGo;
 
 impl Engine for DuckDuckGo {
-    fn get_query_url(&self, query: &str, use_https: bool) -> String {
+    fn get_query_url(&self, query: &str, site: &str, use_https: bool) -> String {
         // For more information about query url, the information here is useful:
         // https://stackoverflow.com/questions/37012469/duckduckgo-api-getting-search-results
         if use_https {
             format!(
-                "https://duckduckgo.com/html?q=site:stackoverflow.com%20{}&t=hj&ia=web",
-                query
+                "https://duckduckgo.com/html?q={}&t=hj&ia=web",
+                encode_query(site, query)
             )
         } else {
             format!(
-                "http://duckduckgo.com/html?q=site:stackoverflow.com%20{}&t=hj&ia=web",
-                query
+                "http://duckduckgo.com/html?q={}&t=hj&ia=web",
+                encode_query(site, query)
             )
         }
     }
 
-    fn extract_links(&self, page: &str) -> Option<Vec<String>> {
+    fn extract_links(&self, page: &str, _site: &str) -> Option<Vec<String>> {
         let doc: Document = Document::from(page);
         let target_elements = doc.find(Class("result__a"));
         let links: Vec<String> = target_elements
             .filter_map(|node| node.attr("href"))
             .filter_map(|link| {
-                if link.starts_with("/l/?") {
+                if let Some(query) = link.strip_prefix("/l/?") {
                     // DuckDuckGo redirect link
                     // e.g. /l/?kh=-1&uddg=https%3A%2F%2Fdoc.rust%2Dlang.org%2Fstd%2Fprimitive.str.html
                     debug!("Extracting URL from redirect link {:?}", link);
-                    let query = &link[4..]; // trim "/l/?"
                     form_urlencoded::parse(query.as_bytes())
                         .find(|(k, _)| k == "uddg")
                         .map(|(_, v)| v.into_owned())
@@ -70,8 +69,8 @@ mod tests {
     </body>
 </html>"#,
         );
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_some(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_some());
         assert_eq!(
             possible_links.unwrap(),
             vec![
@@ -85,8 +84,8 @@ mod tests {
     fn test_extract_links_when_there_are_no_links_available() {
         let engine = DuckDuckGo;
         let page: String = String::from("<html></html>");
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_none(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_none());
     }
 
     #[test]
@@ -105,8 +104,8 @@ mod tests {
 </html>"#,
         );
         let engine = DuckDuckGo;
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_some(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_some());
         assert_eq!(
             possible_links.unwrap(),
             vec![
@@ -132,26 +131,41 @@ mod tests {
         </html>"#,
         );
         let engine = DuckDuckGo;
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_none(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_none());
     }
 
     #[test]
     fn test_get_query_url() {
         let engine = DuckDuckGo;
-        let result: String = engine.get_query_url(&String::from("how to write unit test"), true);
+        let result: String = engine.get_query_url(
+            &String::from("how to write unit test"),
+            "stackoverflow.com",
+            true,
+        );
         assert_eq!(
-            "https://duckduckgo.com/html?q=site:stackoverflow.com%20how to write unit test&t=hj&ia=web",
+            "https://duckduckgo.com/html?q=site%3Astackoverflow.com+how+to+write+unit+test&t=hj&ia=web",
             result
         );
     }
 
+    #[test]
+    fn test_match_in_title() {
+        let engine = DuckDuckGo;
+        let result: String = engine.match_in_title("value moved here");
+        assert_eq!("intitle:value intitle:moved intitle:here", result);
+    }
+
     #[test]
     fn test_get_query_url_with_https_option_disabled() {
         let engine = DuckDuckGo;
-        let result: String = engine.get_query_url(&String::from("how to write unit test"), false);
+        let result: String = engine.get_query_url(
+            &String::from("how to write unit test"),
+            "stackoverflow.com",
+            false,
+        );
         assert_eq!(
-            "http://duckduckgo.com/html?q=site:stackoverflow.com%20how to write unit test&t=hj&ia=web",
+            "http://duckduckgo.com/html?q=site%3Astackoverflow.com+how+to+write+unit+test&t=hj&ia=web",
             result
         );
     }
@@ -1,27 +1,54 @@
-use super::Engine;
+use super::{encode_query, Engine};
 use select::document::Document;
 use select::predicate::{Class, Name, Predicate};
+use url::form_urlencoded;
 
 pub struct Google;
 
 impl Engine for Google {
-    fn get_query_url(&self, query: &str, use_https: bool) -> String {
+    fn get_query_url(&self, query: &str, site: &str, use_https: bool) -> String {
         if use_https {
             format!(
-                "https://www.google.com/search?q=site:stackoverflow.com%20{}",
-                query
+                "https://www.google.com/search?q={}",
+                encode_query(site, query)
             )
         } else {
-            format!(
-                "http://www.bing.com/search?q=site:stackoverflow.com%20{}",
-                query
-            )
+            format!("http://www.bing.com/search?q={}", encode_query(site, query))
+        }
+    }
+
+    fn get_page_url(&self, query: &str, site: &str, use_https: bool, page: u8) -> Option<String> {
+        let url: String = self.get_query_url(query, site, use_https);
+        if page == 0 {
+            return Some(url);
+        }
+        // the http url goes to bing, so only the first page is searched.
+        if !use_https {
+            return None;
         }
+        // each page has 10 results, and google counts results from 0.
+        Some(format!("{}&start={}", url, u32::from(page) * 10))
     }
 
-    fn extract_links(&self, page: &str) -> Option<Vec<String>> {
+    fn extract_links(&self, page: &str, site: &str) -> Option<Vec<String>> {
         let doc: Document = Document::from(page);
-        // use child rather than decendent, because in google search engine
+        // current google result page puts title into link, like this:
+        // <div class="yuRUbf">
+        //   <a href="test_link"><h3>title</h3></a>
+        // </div>
+        let links: Vec<String> = doc
+            .find(Name("a"))
+            .filter(|node| node.find(Name("h3")).next().is_some())
+            .filter_map(|node| node.attr("href"))
+            .filter_map(real_link)
+            .filter(|link| link.contains(site))
+            .collect();
+        if !links.is_empty() {
+            debug!("Links extract from google: {:?}", links);
+            return Some(links);
+        }
+
+        // use child rather than decendent, because in old google search engine
         // a node's structure is like this:
         // <r>
         //   <a href="test_link"></a>
@@ -30,7 +57,7 @@ impl Engine for Google {
         let target_elements = doc.find(Class("r").child(Name("a")));
         let links: Vec<String> = target_elements
             .filter_map(|node| node.attr("href"))
-            .map(String::from)
+            .filter_map(real_link)
             .collect();
 
         debug!("Links extract from google: {:?}", links);
@@ -39,6 +66,47 @@ impl Engine for Google {
         }
         Some(links)
     }
+
+    fn match_in_title(&self, query: &str) -> String {
+        // google supports `allintitle:` operator, which applies to all words.
+        format!("allintitle:{}", query)
+    }
+
+    fn localize_url(
+        &self,
+        url: String,
+        use_https: bool,
+        locale: Option<&str>,
+        region: Option<&str>,
+    ) -> String {
+        // the http url goes to bing, which doesn't know google's parameters.
+        if !use_https {
+            return url;
+        }
+        let params: String = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(locale.map(|locale| ("hl", locale)))
+            .extend_pairs(region.map(|region| ("gl", region)))
+            .finish();
+        if params.is_empty() {
+            url
+        } else {
+            format!("{}&{}", url, params)
+        }
+    }
+}
+
+/// Get the real link from google result link, which may be a redirect link.
+fn real_link(link: &str) -> Option<String> {
+    if let Some(query) = link.strip_prefix("/url?") {
+        // Google redirect link
+        // e.g. /url?q=https://stackoverflow.com/questions/1/parse-json&sa=U
+        debug!("Extracting URL from redirect link {:?}", link);
+        form_urlencoded::parse(query.as_bytes())
+            .find(|(k, _)| k == "q")
+            .map(|(_, v)| v.into_owned())
+    } else {
+        Some(String::from(link))
+    }
 }
 
 #[cfg(test)]
@@ -67,8 +135,8 @@ mod tests {
     </body>
 </html>"#,
         );
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_some(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_some());
         assert_eq!(
             possible_links.unwrap(),
             vec![
@@ -78,20 +146,119 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_extract_links_when_links_are_redirected() {
+        let engine = Google;
+        let page: String = String::from(
+            r#"
+<html>
+    <body>
+        <div class="g">
+            <div class="r">
+                <a href="/url?q=https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json%3Fnoredirect%3D1&amp;sa=U&amp;ved=2ah">
+                </a>
+            </div>
+        </div>
+        <div class="g">
+            <div class="r">
+                <a href="https://stackoverflow.com/questions/2/direct-link">
+                </a>
+            </div>
+        </div>
+    </body>
+</html>"#,
+        );
+        assert_eq!(
+            engine.extract_links(&page, "stackoverflow.com").unwrap(),
+            vec![
+                String::from(
+                    "https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json?noredirect=1"
+                ),
+                String::from("https://stackoverflow.com/questions/2/direct-link"),
+            ]
+        )
+    }
+
+    #[test]
+    fn test_extract_links_from_current_layout() {
+        let engine = Google;
+        let page: String = String::from(
+            r#"
+<html>
+    <body>
+        <div id="search">
+            <div class="g">
+                <div class="yuRUbf">
+                    <a href="https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json" ping="/url?sa=t">
+                        <br>
+                        <h3 class="LC20lb MBeuO DKV0Md">How to parse data in JSON format?</h3>
+                        <div class="notranslate"><cite>https://stackoverflow.com › questions</cite></div>
+                    </a>
+                </div>
+                <div class="VwiC3b"><a href="https://stackoverflow.com/users/1/someone">someone</a></div>
+            </div>
+            <div class="g">
+                <div class="yuRUbf">
+                    <a href="https://www.reddit.com/r/rust/json"><h3>Not stackoverflow</h3></a>
+                </div>
+            </div>
+            <div class="g">
+                <div class="yuRUbf">
+                    <a href="/url?q=https://stackoverflow.com/questions/30292752/how-do-i-parse-a-json-file&amp;sa=U">
+                        <h3>How do I parse a JSON File?</h3>
+                    </a>
+                </div>
+            </div>
+        </div>
+    </body>
+</html>"#,
+        );
+        assert_eq!(
+            engine.extract_links(&page, "stackoverflow.com").unwrap(),
+            vec![
+                String::from(
+                    "https://stackoverflow.com/questions/7771011/how-to-parse-data-in-json"
+                ),
+                String::from(
+                    "https://stackoverflow.com/questions/30292752/how-do-i-parse-a-json-file"
+                ),
+            ]
+        )
+    }
+
     #[test]
     fn test_extract_links_when_there_are_no_links_available() {
         let engine = Google;
         let page: String = String::from("<html></html>");
-        let possible_links: Option<Vec<String>> = engine.extract_links(&page);
-        assert_eq!(possible_links.is_none(), true);
+        let possible_links: Option<Vec<String>> = engine.extract_links(&page, "stackoverflow.com");
+        assert!(possible_links.is_none());
     }
 
     #[test]
     fn test_get_query_url() {
         let engine = Google;
-        let result: String = engine.get_query_url(&String::from("how to write unit test"), true);
+        let result: String = engine.get_query_url(
+            &String::from("how to write unit test"),
+            "stackoverflow.com",
+            true,
+        );
         assert_eq!(
-            "https://www.google.com/search?q=site:stackoverflow.com%20how to write unit test",
+            "https://www.google.com/search?q=site%3Astackoverflow.com+how+to+write+unit+test",
+            result
+        );
+        // special characters of query are encoded, so they don't break the url.
+        assert_eq!(
+            engine.get_query_url("c++ a&b #1 解析", "stackoverflow.com", true),
+            "https://www.google.com/search?q=site%3Astackoverflow.com+c%2B%2B+a%26b+%231+%E8%A7%A3%E6%9E%90"
+        );
+    }
+
+    #[test]
+    fn test_match_in_title() {
+        let engine = Google;
+        let result: String = engine.match_in_title("borrowed value does not live long enough");
+        assert_eq!(
+            "allintitle:borrowed value does not live long enough",
             result
         );
     }
@@ -99,10 +266,74 @@ mod tests {
     #[test]
     fn test_get_query_url_with_https_option_disabled() {
         let engine = Google;
-        let result: String = engine.get_query_url(&String::from("how to write unit test"), false);
+        let result: String = engine.get_query_url(
+            &String::from("how to write unit test"),
+            "stackoverflow.com",
+            false,
+        );
         assert_eq!(
-            "http://www.bing.com/search?q=site:stackoverflow.com%20how to write unit test",
+            "http://www.bing.com/search?q=site%3Astackoverflow.com+how+to+write+unit+test",
             result
         );
     }
+
+    #[test]
+    fn test_localize_url() {
+        let engine = Google;
+        let url: String =
+            String::from("https://www.google.com/search?q=site%3Astackoverflow.com+parse+json");
+        assert_eq!(
+            engine.localize_url(url.clone(), true, Some("en"), Some("us")),
+            format!("{}&hl=en&gl=us", url)
+        );
+        assert_eq!(
+            engine.localize_url(url.clone(), true, None, Some("de")),
+            format!("{}&gl=de", url)
+        );
+        assert_eq!(
+            engine.localize_url(url.clone(), true, Some("zh-CN"), None),
+            format!("{}&hl=zh-CN", url)
+        );
+        assert_eq!(engine.localize_url(url.clone(), true, None, None), url);
+        // http fallback goes to bing.
+        let url: String = engine.get_query_url("parse json", "stackoverflow.com", false);
+        assert_eq!(
+            engine.localize_url(url.clone(), false, Some("en"), Some("us")),
+            url
+        );
+    }
+
+    #[test]
+    fn test_get_page_url() {
+        let engine = Google;
+        assert_eq!(
+            engine.get_page_url("parse json", "stackoverflow.com", true, 0),
+            Some(String::from(
+                "https://www.google.com/search?q=site%3Astackoverflow.com+parse+json"
+            ))
+        );
+        assert_eq!(
+            engine.get_page_url("parse json", "stackoverflow.com", true, 2),
+            Some(String::from(
+                "https://www.google.com/search?q=site%3Astackoverflow.com+parse+json&start=20"
+            ))
+        );
+        assert_eq!(
+            engine.get_page_url("parse json", "stackoverflow.com", false, 0),
+            Some(engine.get_query_url("parse json", "stackoverflow.com", false))
+        );
+        assert_eq!(
+            engine.get_page_url("parse json", "stackoverflow.com", false, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_query_url_of_other_site() {
+        let engine = Google;
+        assert_eq!(
+            engine.get_query_url("ssh tunnel", "superuser.com", true),
+            "https://www.google.com/search?q=site%3Asuperuser.com+ssh+tunnel"
+        );
+    }
 }
@@ -0,0 +1,179 @@
+//! Diagnostics about whether a search engine can be used right now.
+use super::{get_engine, is_blocked_page, Engine};
+use crate::config::{Config, SearchEngine, DEFAULT_SITE};
+use crate::error::Result;
+use crate::utils::{client_builder, random_agent};
+use reqwest::{Client, StatusCode};
+use std::time::{Duration, Instant};
+
+/// Status of the search engine.
+#[derive(Debug, PartialEq)]
+pub enum EngineStatus {
+    /// Search engine works fine.
+    Reachable,
+    /// Search engine thinks that we are robot, and asks for captcha.
+    Blocked,
+    /// Search engine is returning errors, contains the reason.
+    Erroring(String),
+}
+
+/// Health information of a search engine.
+#[derive(Debug)]
+pub struct EngineHealth {
+    status: EngineStatus,
+    latency: Duration,
+}
+
+impl EngineHealth {
+    pub fn status(&self) -> &EngineStatus {
+        &self.status
+    }
+
+    /// How long the engine takes to reply our request.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
+
+/// Check if the given search engine is reachable, the request goes through
+/// the proxy and timeout of user config.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::{self, Config, OutputOption, SearchEngine};
+/// use hors::engine::EngineStatus;
+///
+/// # async fn run() {
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+/// let health = hors::check_engine(SearchEngine::Bing, &conf).await.unwrap();
+/// if health.status() == &EngineStatus::Reachable {
+///     println!("bing replies in {:?}", health.latency());
+/// }
+/// # }
+/// ```
+pub async fn check_engine(search_engine: SearchEngine, conf: &Config) -> Result<EngineHealth> {
+    let client: Client = client_builder(conf)?.build()?;
+    check_engine_with_client(search_engine, &client).await
+}
+
+/// Check if the given search engine is reachable through the given client.
+///
+/// It makes a single cheap search request, there is no retry here.
+///
+/// # Returns
+///
+/// The health information of search engine, network errors are reported as
+/// `EngineStatus::Erroring` rather than an Error.
+pub async fn check_engine_with_client(
+    search_engine: SearchEngine,
+    client: &Client,
+) -> Result<EngineHealth> {
+    let engine: Box<dyn Engine> = get_engine(search_engine);
+    check_url(&engine.get_query_url("hors", DEFAULT_SITE, true), client).await
+}
+
+async fn check_url(url: &str, client: &Client) -> Result<EngineHealth> {
+    let start: Instant = Instant::now();
+    let response = client
+        .get(url)
+        .header(reqwest::header::USER_AGENT, random_agent())
+        .send()
+        .await;
+    let status: EngineStatus = match response {
+        Ok(resp) => {
+            let status_code: StatusCode = resp.status();
+            match resp.text().await {
+                Ok(page) => classify(status_code, &page),
+                Err(err) => EngineStatus::Erroring(err.to_string()),
+            }
+        }
+        Err(err) => EngineStatus::Erroring(err.to_string()),
+    };
+    let latency: Duration = start.elapsed();
+    debug!("Health of {}: {:?}, latency: {:?}", url, status, latency);
+    Ok(EngineHealth { status, latency })
+}
+
+fn classify(status_code: StatusCode, page: &str) -> EngineStatus {
+    if status_code == StatusCode::TOO_MANY_REQUESTS {
+        return EngineStatus::Blocked;
+    }
+    if is_blocked_page(page) {
+        return EngineStatus::Blocked;
+    }
+    if !status_code.is_success() {
+        return EngineStatus::Erroring(format!("Unexpected status code {}", status_code));
+    }
+    EngineStatus::Reachable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputOption;
+    use crate::test_utils::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn test_check_url_when_engine_is_reachable() {
+        let server = MockServer::start(vec![MockResponse::new(200, "<html>results</html>")]);
+        let health: EngineHealth = check_url(&server.url("/search"), &Client::new())
+            .await
+            .unwrap();
+        assert_eq!(health.status(), &EngineStatus::Reachable);
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_url_when_engine_is_blocked() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, r#"<html><div class="g-recaptcha"></div></html>"#),
+            MockResponse::new(429, "slow down"),
+        ]);
+        let client: Client = Client::new();
+        let health: EngineHealth = check_url(&server.url("/search"), &client).await.unwrap();
+        assert_eq!(health.status(), &EngineStatus::Blocked);
+        let health: EngineHealth = check_url(&server.url("/search"), &client).await.unwrap();
+        assert_eq!(health.status(), &EngineStatus::Blocked);
+    }
+
+    #[tokio::test]
+    async fn test_check_engine_through_proxy_of_config() {
+        let server = MockServer::start(vec![MockResponse::new(502, "no tunnel")]);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_proxy(Some(server.url(""))).unwrap();
+
+        let health: EngineHealth = check_engine(SearchEngine::Bing, &conf).await.unwrap();
+
+        assert!(matches!(health.status(), EngineStatus::Erroring(_)));
+        assert!(server.requests()[0].starts_with("CONNECT www.bing.com:443"));
+    }
+
+    #[test]
+    fn test_classify_when_results_mention_captcha_words() {
+        let page: &str = r#"<html><a href="https://stackoverflow.com/questions/1/test">google unusual traffic error</a></html>"#;
+        assert_eq!(classify(StatusCode::OK, page), EngineStatus::Reachable);
+    }
+
+    #[tokio::test]
+    async fn test_check_url_when_engine_is_erroring() {
+        let server = MockServer::start(vec![MockResponse::new(500, "oops")]);
+        let health: EngineHealth = check_url(&server.url("/search"), &Client::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            health.status(),
+            &EngineStatus::Erroring(String::from(
+                "Unexpected status code 500 Internal Server Error"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_url_when_engine_is_unreachable() {
+        let health: EngineHealth = check_url("http://127.0.0.1:1/search", &Client::new())
+            .await
+            .unwrap();
+        assert!(matches!(health.status(), EngineStatus::Erroring(_)));
+    }
+}
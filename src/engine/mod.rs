@@ -1,11 +1,36 @@
 mod bing;
 mod duckduckgo;
 mod google;
+mod health;
+mod stackexchange_api;
 
-use crate::config::SearchEngine;
+use crate::config::{Config, SearchEngine, FAST_PATH_TIMEOUT};
 use crate::error::{Error, Result};
-use crate::utils::random_agent;
-use reqwest::{Client, ClientBuilder, RequestBuilder};
+use crate::retry::send_with_retry;
+use crate::utils::{canonical_question_url, client_builder, question_id, random_agent};
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use std::collections::HashSet;
+use url::form_urlencoded::byte_serialize;
+
+pub use health::{check_engine, check_engine_with_client, EngineHealth, EngineStatus};
+pub(crate) use stackexchange_api::{answers_from, API_URL, MAX_IDS};
+pub use stackexchange_api::{question_answers, ApiAnswer};
+
+/// How many result pages are fetched at most when searching within a site.
+const MAX_SEARCH_PAGES: u8 = 3;
+
+/// Markup of the captcha page which search engine replies when it rate limits
+/// us, they are matched against lowercased page.  Only markup is matched,
+/// because words like "unusual traffic" may be in the query or results too.
+const BLOCKED_MARKERS: [&str; 3] = ["id=\"captcha-form\"", "g-recaptcha", "anomaly-modal"];
+
+/// Check if the page is a captcha page rather than search results.
+fn is_blocked_page(page: &str) -> bool {
+    let lowercase_page: String = page.to_lowercase();
+    BLOCKED_MARKERS
+        .iter()
+        .any(|marker| lowercase_page.contains(marker))
+}
 
 /// Search engine trait
 pub trait Engine {
@@ -14,21 +39,80 @@ pub trait Engine {
     /// # Arguments
     ///
     /// * `query` - The user input query information.
+    /// * `site` - The StackExchange site to search within, like `stackoverflow.com`.
     /// * `use_https` - Return query url which is https scheme or http scheme.
     ///
     /// # Returns
     ///
     /// Return the query url, which can be fired with HTTP GET request.
-    fn get_query_url(&self, query: &str, use_https: bool) -> String;
+    fn get_query_url(&self, query: &str, site: &str, use_https: bool) -> String;
+
+    /// Get url of the given result page, the first page is `0`.
+    ///
+    /// By default only the first page is supported, the search engine should
+    /// override it if it supports pagination.
+    ///
+    /// # Returns
+    ///
+    /// Return the query url of the page, or None if the page isn't supported.
+    fn get_page_url(&self, query: &str, site: &str, use_https: bool, page: u8) -> Option<String> {
+        if page == 0 {
+            Some(self.get_query_url(query, site, use_https))
+        } else {
+            None
+        }
+    }
 
-    /// Extract stackoverflow links from given page.
+    /// Extract links of the site from given page.
     ///
     /// # Arguments
     ///
     /// * `page` - the search result page, which is mainly fetched from `http GET` method.
+    /// * `site` - The StackExchange site which is searched within.
     ///
     /// Links to the relative question, or returns None if we can't find it.
-    fn extract_links(&self, pages: &str) -> Option<Vec<String>>;
+    fn extract_links(&self, pages: &str, site: &str) -> Option<Vec<String>>;
+
+    /// Make the query only matches question titles.
+    ///
+    /// By default each word is prefixed with `intitle:` operator, the search engine
+    /// should override it if it supports better operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The user input query information.
+    ///
+    /// # Returns
+    ///
+    /// The query which can be passed to `get_query_url`.
+    fn match_in_title(&self, query: &str) -> String {
+        query
+            .split_whitespace()
+            .map(|word| format!("intitle:{}", word))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Bias the query url toward user's locale and region.
+    ///
+    /// By default the url is returned as it is, the search engine should
+    /// override it if it supports them.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The url which is got from `get_page_url`.
+    /// * `use_https` - If the url is https scheme or http scheme.
+    /// * `locale` - Language of search results, like `en`.
+    /// * `region` - Country of search results, like `us`.
+    fn localize_url(
+        &self,
+        url: String,
+        _use_https: bool,
+        _locale: Option<&str>,
+        _region: Option<&str>,
+    ) -> String {
+        url
+    }
 }
 
 /// Search result links under the given search engine.
@@ -40,12 +124,14 @@ pub trait Engine {
 /// ```rust
 /// # async fn run() {
 /// use std::str::FromStr;
-/// use hors::{self, SearchEngine};
+/// use hors::{self, Config, OutputOption, SearchEngine};
 ///
 /// let search_engine: SearchEngine = SearchEngine::from_str("bing").unwrap();
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
 /// let target_links: Vec<String> = hors::search_links(
 ///     "how to parse json in rust",
-///     search_engine
+///     search_engine,
+///     &conf
 /// )
 /// .await
 /// .unwrap();
@@ -55,10 +141,28 @@ pub trait Engine {
 /// }
 /// # }
 /// ```
-pub async fn search_links(query: &str, search_engine: SearchEngine) -> Result<Vec<String>> {
-    let client: Client = ClientBuilder::new().cookie_store(true).build()?;
+///
+/// # Returns
+///
+/// Links to questions of `conf.sites()`, the most relevant goes first.  Only
+/// the search result page is fetched, answer pages are not, so the links can be
+/// passed to `get_answers` or rendered by the caller.
+///
+/// Links are normalized: query strings and fragments are stripped, mobile hosts
+/// like `m.stackoverflow.com` are collapsed, and the duplicated links are removed.
+/// Links of `conf.blocked_hosts()` are removed too.
+///
+/// `Error::NoResult` is returned if no link can be found, `Error::RateLimited`
+/// if search engine rate limits us, or `Error::LowConfidence` if confidence
+/// guard is on and links seem unrelated to the query.
+pub async fn search_links(
+    query: &str,
+    search_engine: SearchEngine,
+    conf: &Config,
+) -> Result<Vec<String>> {
+    let client: Client = client_builder(conf)?.build()?;
 
-    search_links_with_client(query, search_engine, &client).await
+    search_links_with_client(query, search_engine, conf, &client).await
 }
 
 /// Search result links under the given search engine.
@@ -74,11 +178,13 @@ pub async fn search_links(query: &str, search_engine: SearchEngine) -> Result<Ve
 ///
 /// # async fn run() {
 /// let search_engine: SearchEngine = SearchEngine::from_str("bing").unwrap();
+/// let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
 /// // please make sure that `cookie_store` should set to `true` in client builder.
 /// let mut client: Client = ClientBuilder::new().cookie_store(true).build().unwrap();
 /// let target_links: Vec<String> = hors::search_links_with_client(
 ///     "how to parse json in rust",
 ///     search_engine,
+///     &conf,
 ///     &client
 /// )
 /// .await
@@ -97,33 +203,263 @@ pub async fn search_links(query: &str, search_engine: SearchEngine) -> Result<Ve
 pub async fn search_links_with_client(
     query: &str,
     search_engine: SearchEngine,
+    conf: &Config,
     client: &Client,
 ) -> Result<Vec<String>> {
-    let https_opts: Vec<bool> = vec![true, false];
-    let engine: Box<dyn Engine> = match search_engine {
-        SearchEngine::Bing => Box::new(bing::Bing),
-        SearchEngine::Google => Box::new(google::Google),
-        SearchEngine::DuckDuckGo => Box::new(duckduckgo::DuckDuckGo),
+    let engine: Box<dyn Engine> = get_engine(search_engine);
+    let origin_query: &str = query;
+    let query: String = if conf.match_in_title() {
+        engine.match_in_title(query)
+    } else {
+        query.to_string()
     };
 
+    let mut sites_links: Vec<Vec<String>> = vec![];
+    let mut rate_limited: Option<Error> = None;
+    if let SearchEngine::StackExchange = search_engine {
+        let api_links: Vec<Vec<String>> =
+            stackexchange_api::search_links(origin_query, conf, client, stackexchange_api::API_URL)
+                .await?;
+        sites_links.extend(api_links.into_iter().filter(|links| !links.is_empty()));
+    } else {
+        for site in conf.sites() {
+            match search_site(&query, site, &*engine, conf, client).await {
+                Ok(Some(links)) => sites_links.push(links),
+                Ok(None) => warn!("Can't find search result in site {}", site),
+                Err(err) => {
+                    warn!("Can't search in site {}: {}", site, err);
+                    rate_limited = Some(err);
+                }
+            }
+        }
+    }
+    if sites_links.is_empty() {
+        // tell user that it's rate limited, rather than the query has no result.
+        return Err(rate_limited.unwrap_or_else(|| Error::NoResult(origin_query.to_string())));
+    }
+    let links: Vec<String> = dedup_links(merge_links(sites_links));
+    if conf.confidence_guard() && is_low_confidence(origin_query, &links) {
+        return Err(Error::LowConfidence(links));
+    }
+    Ok(links)
+}
+
+/// Search links within the given site, returns None if nothing is found.
+///
+/// `Error::RateLimited` is returned if nothing is found because search engine
+/// rate limits us.
+///
+/// More result pages are fetched if the engine supports pagination, until
+/// there are `conf.numbers()` question links or `MAX_SEARCH_PAGES` are fetched.
+/// Question links are put before other links, like tag pages, so they aren't
+/// crowded out of the top links.
+async fn search_site(
+    query: &str,
+    site: &str,
+    engine: &dyn Engine,
+    conf: &Config,
+    client: &Client,
+) -> Result<Option<Vec<String>>> {
+    // fallback to http is a retry, which is skipped under fast path.
+    let https_opts: Vec<bool> = if conf.fast_path() {
+        vec![true]
+    } else {
+        vec![true, false]
+    };
+    let mut rate_limited: Option<Error> = None;
     for opt in https_opts {
-        let fetch_url: String = get_query_url(query, &*engine, opt);
-        let page: Result<String> = fetch(&fetch_url, client).await;
-        match page {
-            Ok(page) => {
-                let extract_results = extract_links(&page, &*engine);
-                if let Some(links) = extract_results {
-                    return Ok(links);
+        let mut links: Vec<String> = vec![];
+        for page in 0..MAX_SEARCH_PAGES {
+            let fetch_url: String = match engine.get_page_url(query, site, opt, page) {
+                Some(url) => {
+                    engine.localize_url(url, opt, conf.search_locale(), conf.search_region())
+                }
+                None => break,
+            };
+            let page: String = match fetch(&fetch_url, client, conf).await {
+                Ok(page) => page,
+                Err(e @ Error::RateLimited(_)) => {
+                    warn!("Rate limited when get url {}", fetch_url);
+                    rate_limited = Some(e);
+                    break;
                 }
+                Err(e) => {
+                    warn!("Erorr for get url {}: {}", fetch_url, e);
+                    break;
+                }
+            };
+            let page_links: Vec<String> = extract_links(&page, site, engine)
+                .map(|links| remove_blocked_hosts(links, conf.blocked_hosts()))
+                .unwrap_or_default();
+            if page_links.is_empty() {
+                break;
+            }
+            links.extend(page_links);
+            let questions: usize = links
+                .iter()
+                .filter(|link| question_id(link).is_some())
+                .count();
+            if questions >= conf.numbers() as usize {
+                break;
             }
-            Err(e) => warn!("Erorr for get url {}: {}", fetch_url, e),
+            debug!(
+                "Only {} question links are found, go to next page",
+                questions
+            );
+        }
+        if !links.is_empty() {
+            // the sort is stable, so the order of question links is kept.
+            links.sort_by_key(|link| question_id(link).is_none());
+            return Ok(Some(links));
         }
     }
-    Err(Error::from_parse("Can't find search result..."))
+    match rate_limited {
+        Some(err) => Err(err),
+        None => Ok(None),
+    }
 }
 
-fn get_query_url(query: &str, search_engine: &dyn Engine, use_https: bool) -> String {
-    search_engine.get_query_url(query, use_https)
+/// Search the query without fetching any answer pages, to diagnose which
+/// step breaks when the search gets nothing.
+///
+/// Only the first result page of each site is fetched, and it isn't retried
+/// with http.
+///
+/// # Returns
+///
+/// The report which contains the query url of each site and the links
+/// extracted from its result page, or an Error if the StackExchange API fails.
+pub async fn dry_run_with_client(
+    query: &str,
+    search_engine: SearchEngine,
+    conf: &Config,
+    client: &Client,
+) -> Result<String> {
+    if let SearchEngine::StackExchange = search_engine {
+        // the api gives links directly, there is no result page.
+        let links: Vec<String> =
+            stackexchange_api::search_links(query, conf, client, stackexchange_api::API_URL)
+                .await?
+                .concat();
+        return Ok(format_dry_run("StackExchange API", Ok(links)));
+    }
+    let engine: Box<dyn Engine> = get_engine(search_engine);
+    let query: String = if conf.match_in_title() {
+        engine.match_in_title(query)
+    } else {
+        query.to_string()
+    };
+    let mut reports: Vec<String> = vec![];
+    for site in conf.sites() {
+        reports.push(dry_run_site(&query, site, &*engine, conf, client).await);
+    }
+    Ok(reports.join("\n\n"))
+}
+
+async fn dry_run_site(
+    query: &str,
+    site: &str,
+    engine: &dyn Engine,
+    conf: &Config,
+    client: &Client,
+) -> String {
+    let url: String = engine.localize_url(
+        engine.get_query_url(query, site, true),
+        true,
+        conf.search_locale(),
+        conf.search_region(),
+    );
+    let links: Result<Vec<String>> = fetch(&url, client, conf)
+        .await
+        .map(|page| extract_links(&page, site, engine).unwrap_or_default());
+    format!(
+        "Query url for {}: {}\n{}",
+        site,
+        url,
+        format_dry_run("the result page", links)
+    )
+}
+
+fn format_dry_run(source: &str, links: Result<Vec<String>>) -> String {
+    match links {
+        Ok(links) if links.is_empty() => format!("No links are extracted from {}.", source),
+        Ok(links) => {
+            let mut lines: Vec<String> = vec![format!(
+                "{} links are extracted from {}:",
+                links.len(),
+                source
+            )];
+            lines.extend(links.iter().map(|link| format!("  {}", link)));
+            lines.join("\n")
+        }
+        Err(err) => format!("Can't get links from {}: {}", source, err),
+    }
+}
+
+/// Merge links from several sites, each site take turns to give it's next link,
+/// so the best links of all sites go first.
+fn merge_links(sites_links: Vec<Vec<String>>) -> Vec<String> {
+    let mut iters: Vec<_> = sites_links.into_iter().map(Vec::into_iter).collect();
+    let mut links: Vec<String> = vec![];
+    loop {
+        let merged: usize = links.len();
+        links.extend(iters.iter_mut().filter_map(Iterator::next));
+        if links.len() == merged {
+            return links;
+        }
+    }
+}
+
+/// Normalize links and remove duplicated ones, the first seen link is kept.
+///
+/// Links to the same question are duplicated even if their slugs are different.
+fn dedup_links(links: Vec<String>) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::new();
+    links
+        .into_iter()
+        .map(|link| normalize_link(&link))
+        .filter(|link| seen.insert(canonical_question_url(link).unwrap_or_else(|| link.clone())))
+        .collect()
+}
+
+/// Strip query string and fragment of the link, and collapse mobile host like
+/// `m.stackoverflow.com` to `stackoverflow.com`.
+///
+/// The link is returned as it is if it's not a valid url.
+fn normalize_link(link: &str) -> String {
+    let mut url: Url = match Url::parse(link) {
+        Ok(url) => url,
+        Err(_) => return link.to_string(),
+    };
+    url.set_query(None);
+    url.set_fragment(None);
+    if let Some(host) = url.host_str().and_then(|host| host.strip_prefix("m.")) {
+        let host: String = host.to_string();
+        if url.set_host(Some(&host)).is_err() {
+            return link.to_string();
+        }
+    }
+    url.to_string()
+}
+
+/// Make the percent-encoded `q` parameter of search url, which searches the
+/// query within the site, so characters like `&` and `#` in query can't break
+/// the url.
+fn encode_query(site: &str, query: &str) -> String {
+    encode(&format!("site:{} {}", site, query))
+}
+
+fn encode(text: &str) -> String {
+    byte_serialize(text.as_bytes()).collect()
+}
+
+fn get_engine(search_engine: SearchEngine) -> Box<dyn Engine> {
+    match search_engine {
+        SearchEngine::Bing => Box::new(bing::Bing),
+        SearchEngine::Google => Box::new(google::Google),
+        SearchEngine::DuckDuckGo => Box::new(duckduckgo::DuckDuckGo),
+        SearchEngine::StackExchange => Box::new(stackexchange_api::StackExchange),
+    }
 }
 
 /// Fetch actual page according to given url.
@@ -132,19 +468,30 @@ fn get_query_url(query: &str, search_engine: &dyn Engine, use_https: bool) -> St
 ///
 /// * `search_url` - The url which should lead to search result page.
 /// * `client` - An instance of `request::Client` object which can use to fire http request,
-///              please ensure that it's build with cookie_store(true) option.
+///   please ensure that it's build with cookie_store(true) option.
+/// * `conf` - User config, the request times out quickly under fast path.
 ///
 /// # Returns
 ///
 /// If get search result page successfully, it will return the content of page,
-/// or returns error.
-async fn fetch(search_url: &str, client: &Client) -> Result<String> {
-    let request: RequestBuilder = client
+/// or returns error.  `Error::RateLimited` is returned if the status is 429 or
+/// the page is a captcha page.
+async fn fetch(search_url: &str, client: &Client, conf: &Config) -> Result<String> {
+    let mut request: RequestBuilder = client
         .get(search_url)
         .header(reqwest::header::USER_AGENT, random_agent());
+    if conf.fast_path() {
+        request = request.timeout(FAST_PATH_TIMEOUT);
+    }
     debug!("Request to bing information: {:?}", request);
-    let res = request.send().await?;
+    let res = send_with_retry(request, conf).await?;
+    if res.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(Error::RateLimited(search_url.to_string()));
+    }
     let page: String = res.text().await?;
+    if is_blocked_page(&page) {
+        return Err(Error::RateLimited(search_url.to_string()));
+    }
     Ok(page)
 }
 
@@ -153,11 +500,469 @@ async fn fetch(search_url: &str, client: &Client) -> Result<String> {
 /// # Arguments
 ///
 /// * `page` - the search result page, which is mainly got by `fetch` function.
+/// * `site` - the StackExchange site which is searched within.
 /// * `search_engine` - indicate which search engine we can use to extract links out.
 ///
 /// # Returns
 ///
 /// Links to the relative question, or returns None if we can't find it.
-fn extract_links(page: &str, search_engine: &dyn Engine) -> Option<Vec<String>> {
-    search_engine.extract_links(page)
+fn extract_links(page: &str, site: &str, search_engine: &dyn Engine) -> Option<Vec<String>> {
+    search_engine.extract_links(page, site)
+}
+
+/// Common words which say nothing about the topic of query.
+const STOP_WORDS: [&str; 24] = [
+    "an", "and", "are", "as", "at", "be", "by", "can", "do", "does", "for", "from", "how", "in",
+    "is", "it", "my", "of", "on", "or", "the", "to", "what", "with",
+];
+
+/// Check if the top links are unrelated to the query, which means the query
+/// is likely too vague.
+///
+/// The words of query are compared with the slug of question link, like
+/// `parse-json-in-python` in `https://stackoverflow.com/questions/1/parse-json-in-python`.
+/// It's low confidence if none of the top links shares enough words with the query,
+/// links without slug are not counted.
+fn is_low_confidence(query: &str, links: &[String]) -> bool {
+    const TOP_LINKS: usize = 3;
+    const MIN_OVERLAP: f32 = 0.2;
+
+    let query_words: Vec<String> = topic_words(query);
+    if query_words.is_empty() {
+        return false;
+    }
+    let overlaps: Vec<f32> = links
+        .iter()
+        .filter_map(|link| question_slug(link))
+        .take(TOP_LINKS)
+        .map(|slug| {
+            let slug_words: Vec<String> = topic_words(&slug);
+            let shared: usize = query_words
+                .iter()
+                .filter(|word| slug_words.contains(word))
+                .count();
+            shared as f32 / query_words.len() as f32
+        })
+        .collect();
+    if overlaps.is_empty() {
+        debug!("None of the links has slug to compare with query.");
+        return false;
+    }
+    let best_overlap: f32 = overlaps.into_iter().fold(0.0, f32::max);
+    debug!("Best overlap between query and links: {}", best_overlap);
+    best_overlap < MIN_OVERLAP
+}
+
+/// Get the slug of question link, like `parse-json-in-python` in
+/// `https://stackoverflow.com/questions/1/parse-json-in-python`.
+fn question_slug(link: &str) -> Option<String> {
+    let url: Url = Url::parse(link).ok()?;
+    let mut segments = url.path_segments()?;
+    if segments.next()? != "questions" {
+        return None;
+    }
+    segments
+        .nth(1)
+        .filter(|slug| !slug.is_empty())
+        .map(String::from)
+}
+
+/// Get the words of text which are about its topic, stop words are ignored.
+fn topic_words(text: &str) -> Vec<String> {
+    words(text)
+        .into_iter()
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Split text into lowercase words, single character words are ignored.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 1)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Remove links which host is blocked by user.
+///
+/// # Arguments
+///
+/// * `links` - links extracted from search result page.
+/// * `blocked_hosts` - hosts which user doesn't want, their subdomains are blocked too.
+///
+/// # Returns
+///
+/// Links which are not blocked.
+fn remove_blocked_hosts(links: Vec<String>, blocked_hosts: &[String]) -> Vec<String> {
+    if blocked_hosts.is_empty() {
+        return links;
+    }
+    links
+        .into_iter()
+        .filter(|link| {
+            let url: Url = match Url::parse(link) {
+                Ok(url) => url,
+                Err(_) => return true,
+            };
+            let host: &str = url.host_str().unwrap_or_default();
+            let blocked: bool = blocked_hosts.iter().any(|blocked| {
+                host.eq_ignore_ascii_case(blocked)
+                    || host
+                        .to_lowercase()
+                        .ends_with(&format!(".{}", blocked.to_lowercase()))
+            });
+            if blocked {
+                debug!("Link {} is removed, because it's host is blocked", link);
+            }
+            !blocked
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputOption;
+    use crate::test_utils::{MockResponse, MockServer};
+    use reqwest::{ClientBuilder, Proxy};
+
+    #[tokio::test]
+    async fn test_search_links_with_client() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <li class="b_algo">
+                    <h2><a href="https://stackoverflow.com/questions/1/parse-json?rq=1">parse json</a></h2>
+                </li>
+                <li class="b_algo">
+                    <h2><a href="https://m.stackoverflow.com/questions/1/parse-json">parse json</a></h2>
+                </li>
+                <li class="b_algo">
+                    <h2><a href="https://stackoverflow.com/questions/2/json-in-rust">json in rust</a></h2>
+                </li>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        // the https request can't be tunneled through mock server, so it falls
+        // back to http.
+        let client: Client = ClientBuilder::new()
+            .proxy(Proxy::all(&server.url("")).unwrap())
+            .build()
+            .unwrap();
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let links: Vec<String> =
+            search_links_with_client("parse json", SearchEngine::Bing, &conf, &client)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            links,
+            vec![
+                String::from("https://stackoverflow.com/questions/1/parse-json"),
+                String::from("https://stackoverflow.com/questions/2/json-in-rust"),
+            ]
+        );
+        assert!(server.requests().iter().any(|request| request
+            .starts_with("GET http://www.bing.com/search?q=site%3Astackoverflow.com+parse+json ")));
+    }
+
+    /// Engine which searches google result pages of the mock server.
+    struct PagedEngine {
+        server_url: String,
+    }
+
+    impl Engine for PagedEngine {
+        fn get_query_url(&self, _query: &str, _site: &str, _use_https: bool) -> String {
+            self.server_url.clone()
+        }
+
+        fn get_page_url(
+            &self,
+            _query: &str,
+            _site: &str,
+            _use_https: bool,
+            page: u8,
+        ) -> Option<String> {
+            Some(format!(
+                "{}?start={}",
+                self.server_url,
+                u32::from(page) * 10
+            ))
+        }
+
+        fn extract_links(&self, page: &str, site: &str) -> Option<Vec<String>> {
+            google::Google.extract_links(page, site)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_site_goes_to_next_page() {
+        let first_page: &str = r#"
+        <html>
+            <body>
+                <a href="https://stackoverflow.com/tags/json"><h3>json tag</h3></a>
+                <a href="https://stackoverflow.com/users/1/hors"><h3>hors</h3></a>
+            </body>
+        </html>
+        "#;
+        let second_page: &str = r#"
+        <html>
+            <body>
+                <a href="https://stackoverflow.com/questions/1/parse-json"><h3>parse json</h3></a>
+                <a href="https://stackoverflow.com/questions/2/json-in-rust"><h3>json in rust</h3></a>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, first_page),
+            MockResponse::new(200, second_page),
+            MockResponse::new(500, "unexpected"),
+        ]);
+        let engine = PagedEngine {
+            server_url: server.url("/search"),
+        };
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 2, false);
+        conf.set_max_retries(0);
+
+        let links: Option<Vec<String>> = search_site(
+            "parse json",
+            "stackoverflow.com",
+            &engine,
+            &conf,
+            &Client::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            links,
+            Some(vec![
+                String::from("https://stackoverflow.com/questions/1/parse-json"),
+                String::from("https://stackoverflow.com/questions/2/json-in-rust"),
+                String::from("https://stackoverflow.com/tags/json"),
+                String::from("https://stackoverflow.com/users/1/hors"),
+            ])
+        );
+        let requests: Vec<String> = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].starts_with("GET /search?start=10 "));
+    }
+
+    #[test]
+    fn test_encode_query() {
+        assert_eq!(
+            encode_query("stackoverflow.com", "parse json"),
+            "site%3Astackoverflow.com+parse+json"
+        );
+        assert_eq!(
+            encode_query("stackoverflow.com", "move & copy #rust"),
+            "site%3Astackoverflow.com+move+%26+copy+%23rust"
+        );
+        assert_eq!(
+            encode_query("stackoverflow.com", "c++ std::map"),
+            "site%3Astackoverflow.com+c%2B%2B+std%3A%3Amap"
+        );
+        assert_eq!(
+            encode_query("stackoverflow.com", "json 解析"),
+            "site%3Astackoverflow.com+json+%E8%A7%A3%E6%9E%90"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_site() {
+        let page: &str = r#"
+        <html>
+            <body>
+                <a href="https://stackoverflow.com/questions/1/parse-json"><h3>parse json</h3></a>
+                <a href="https://stackoverflow.com/questions/2/json-in-rust"><h3>json in rust</h3></a>
+            </body>
+        </html>
+        "#;
+        let server = MockServer::start(vec![
+            MockResponse::new(200, page),
+            MockResponse::new(200, "<html></html>"),
+            MockResponse::new(429, "too many requests"),
+        ]);
+        let engine = PagedEngine {
+            server_url: server.url("/search"),
+        };
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_max_retries(0);
+        let client: Client = Client::new();
+        let dry_run = || dry_run_site("parse json", "stackoverflow.com", &engine, &conf, &client);
+
+        assert_eq!(
+            dry_run().await,
+            format!(
+                "Query url for stackoverflow.com: {}
+2 links are extracted from the result page:
+  https://stackoverflow.com/questions/1/parse-json
+  https://stackoverflow.com/questions/2/json-in-rust",
+                server.url("/search")
+            )
+        );
+        assert!(dry_run()
+            .await
+            .ends_with("\nNo links are extracted from the result page."));
+        assert!(dry_run()
+            .await
+            .contains("\nCan't get links from the result page: Rate limited by search engine"));
+        // only the result pages are fetched.
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_search_site_when_rate_limited() {
+        let server = MockServer::start(vec![MockResponse::new(429, "too many requests")]);
+        let engine = PagedEngine {
+            server_url: server.url("/search"),
+        };
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_max_retries(0);
+
+        let result: Result<Option<Vec<String>>> = search_site(
+            "parse json",
+            "stackoverflow.com",
+            &engine,
+            &conf,
+            &Client::new(),
+        )
+        .await;
+
+        match result {
+            Err(Error::RateLimited(link)) => assert!(link.starts_with(&server.url("/search"))),
+            other => panic!("Rate limited error is expected, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_when_captcha_page_is_replied() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"<html><body><form id="captcha-form">Our systems have detected unusual traffic from your computer network.</form></body></html>"#,
+        )]);
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let result: Result<String> = fetch(&server.url("/search"), &Client::new(), &conf).await;
+
+        assert!(matches!(result, Err(Error::RateLimited(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_when_results_mention_captcha_words() {
+        let page: &str = r#"<html><body><li class="b_algo"><h2><a href="https://stackoverflow.com/questions/1/google-unusual-traffic-error">Google unusual traffic error, how to solve the captcha?</a></h2></li></body></html>"#;
+        let server = MockServer::start(vec![MockResponse::new(200, page)]);
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let result: Result<String> = fetch(&server.url("/search"), &Client::new(), &conf).await;
+
+        assert_eq!(result.unwrap(), page);
+    }
+
+    #[test]
+    fn test_remove_blocked_hosts() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/test"),
+            String::from("https://sofscraper.com/questions/1/test"),
+            String::from("https://www.SOFScraper.com/questions/2/test"),
+            String::from("https://notsofscraper.com/questions/3/test"),
+        ];
+        let blocked_hosts: Vec<String> = vec![String::from("sofscraper.com")];
+        assert_eq!(
+            remove_blocked_hosts(links, &blocked_hosts),
+            vec![
+                String::from("https://stackoverflow.com/questions/1/test"),
+                String::from("https://notsofscraper.com/questions/3/test"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedup_links() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/test?noredirect=1"),
+            String::from("https://m.stackoverflow.com/questions/1/test"),
+            String::from("https://stackoverflow.com/questions/2/test#answer-3"),
+            String::from("https://stackoverflow.com/questions/1/test-amp"),
+            String::from("https://stackoverflow.com/questions/2/test"),
+            String::from("not a url"),
+        ];
+        assert_eq!(
+            dedup_links(links),
+            vec![
+                String::from("https://stackoverflow.com/questions/1/test"),
+                String::from("https://stackoverflow.com/questions/2/test"),
+                String::from("not a url"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_links_from_two_sites() {
+        let stackoverflow_links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/test"),
+            String::from("https://stackoverflow.com/questions/2/test"),
+            String::from("https://stackoverflow.com/questions/3/test"),
+        ];
+        let serverfault_links: Vec<String> =
+            vec![String::from("https://serverfault.com/questions/4/test")];
+        assert_eq!(
+            merge_links(vec![stackoverflow_links, serverfault_links]),
+            vec![
+                String::from("https://stackoverflow.com/questions/1/test"),
+                String::from("https://serverfault.com/questions/4/test"),
+                String::from("https://stackoverflow.com/questions/2/test"),
+                String::from("https://stackoverflow.com/questions/3/test"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_low_confidence_when_links_are_off_topic() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/center-a-div-horizontally"),
+            String::from("https://stackoverflow.com/questions/2/undo-git-commit"),
+            String::from("https://stackoverflow.com/questions/3/java-string-equals"),
+            String::from("https://stackoverflow.com/questions/4/parse-json-in-rust"),
+        ];
+        assert!(is_low_confidence("parse json in rust", &links));
+    }
+
+    #[test]
+    fn test_is_low_confidence_when_links_are_on_topic() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/questions/1/center-a-div-horizontally"),
+            String::from("https://stackoverflow.com/questions/2/how-to-parse-json-in-rust"),
+        ];
+        assert!(!is_low_confidence("parse JSON in Rust", &links));
+    }
+
+    #[test]
+    fn test_is_low_confidence_ignores_stop_words() {
+        let links: Vec<String> = vec![String::from(
+            "https://stackoverflow.com/questions/1/how-to-center-a-div-in-css",
+        )];
+        assert!(is_low_confidence("how to parse json in rust", &links));
+    }
+
+    #[test]
+    fn test_is_low_confidence_skips_links_without_slug() {
+        let links: Vec<String> = vec![
+            String::from("https://stackoverflow.com/a/123"),
+            String::from("https://stackoverflow.com/questions/2"),
+            String::from("https://example.com/blog/2/parse-json"),
+            String::from("https://stackoverflow.com/questions/3/undo-git-commit"),
+            String::from("https://stackoverflow.com/questions/4/parse-json-in-rust"),
+        ];
+        assert!(!is_low_confidence("parse json in rust", &links));
+        assert!(!is_low_confidence("parse json in rust", &links[..2]));
+    }
+
+    #[test]
+    fn test_remove_blocked_hosts_when_nothing_is_blocked() {
+        let links: Vec<String> = vec![String::from("https://stackoverflow.com/questions/1/test")];
+        assert_eq!(remove_blocked_hosts(links.clone(), &[]), links);
+    }
 }
@@ -0,0 +1,371 @@
+//! Search through the official Stack Exchange API.
+//!
+//! The API is a stable JSON contract, so it doesn't break when stackoverflow
+//! changes its html markup.  See https://api.stackexchange.com/docs for details.
+use super::{encode, Engine};
+use crate::config::Config;
+use crate::error::{Error, Result};
+use once_cell::sync::Lazy;
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::delay_for;
+
+pub const API_URL: &str = "https://api.stackexchange.com/2.3";
+/// Filter which includes the fields hors needs, including answer body.
+const DEFAULT_FILTER: &str = "withbody";
+/// Error id which means that the request quota of api is used up.
+const QUOTA_EXHAUSTED: u32 = 502;
+/// Max number of ids which can be given in one request, it's the max page size too.
+pub const MAX_IDS: usize = 100;
+
+/// When the next request to each api url can be fired, the `backoff` of api
+/// is shared by all requests of the process.
+static NEXT_REQUESTS: Lazy<Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub struct StackExchange;
+
+impl Engine for StackExchange {
+    fn get_query_url(&self, query: &str, site: &str, _use_https: bool) -> String {
+        // the api only supports https.
+        format!(
+            "{}/search/advanced?order=desc&sort=relevance&q={}&site={}",
+            API_URL,
+            encode(query),
+            api_site(site)
+        )
+    }
+
+    fn extract_links(&self, page: &str, _site: &str) -> Option<Vec<String>> {
+        let response: ApiResponse<Question> = serde_json::from_str(page).ok()?;
+        let links: Vec<String> = response.items.into_iter().map(|item| item.link).collect();
+        debug!("Links extract from stackexchange api: {:?}", links);
+        if links.is_empty() {
+            return None;
+        }
+        Some(links)
+    }
+}
+
+/// Wrapper object of all api responses.
+#[derive(Deserialize, Debug)]
+struct ApiResponse<T> {
+    #[serde(default = "Vec::new")]
+    items: Vec<T>,
+    /// Seconds to wait before hitting the same method again.
+    backoff: Option<u64>,
+    error_id: Option<u32>,
+    error_message: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Question {
+    link: String,
+}
+
+/// An answer returned by the api.
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ApiAnswer {
+    pub answer_id: u64,
+    pub question_id: u64,
+    pub score: i32,
+    pub is_accepted: bool,
+    /// Html body of the answer, it's only returned with a filter which includes it.
+    pub body: Option<String>,
+}
+
+/// Client of the api, which respects the `backoff` of api.
+struct Api<'a> {
+    client: &'a Client,
+    api_url: &'a str,
+    conf: &'a Config,
+}
+
+impl<'a> Api<'a> {
+    fn new(client: &'a Client, api_url: &'a str, conf: &'a Config) -> Api<'a> {
+        Api {
+            client,
+            api_url,
+            conf,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, params: &str) -> Result<Vec<T>> {
+        let next_request: Option<Instant> = NEXT_REQUESTS
+            .lock()
+            .expect("Backoff of api should be locked")
+            .remove(self.api_url);
+        if let Some(next_request) = next_request {
+            let now: Instant = Instant::now();
+            if next_request > now {
+                debug!("Wait for backoff of api: {:?}", next_request - now);
+                delay_for(next_request - now).await;
+            }
+        }
+        let mut url: String = format!(
+            "{}{}?{}&filter={}",
+            self.api_url,
+            path,
+            params,
+            encode(self.conf.se_filter().unwrap_or(DEFAULT_FILTER))
+        );
+        if let Some(key) = self.conf.se_api_key() {
+            url = format!("{}&key={}", url, encode(key));
+        }
+        let resp: Response = self.client.get(&url).send().await?;
+        debug!("Response status from stackexchange api: {:?}", resp);
+        let response: ApiResponse<T> = resp.json().await?;
+
+        if let Some(backoff) = response.backoff {
+            NEXT_REQUESTS
+                .lock()
+                .expect("Backoff of api should be locked")
+                .insert(
+                    self.api_url.to_string(),
+                    Instant::now() + Duration::from_secs(backoff),
+                );
+        }
+        match response.error_id {
+            Some(QUOTA_EXHAUSTED) => Err(Error::QuotaExhausted(
+                response.error_message.unwrap_or_default(),
+            )),
+            Some(error_id) => Err(Error::Api(format!(
+                "{} {}",
+                error_id,
+                response.error_message.unwrap_or_default()
+            ))),
+            None => Ok(response.items),
+        }
+    }
+}
+
+/// Search links through the api, within each site in `conf.sites()`.
+///
+/// # Returns
+///
+/// Links of each site, or returns error if the api fails.
+pub async fn search_links(
+    query: &str,
+    conf: &Config,
+    client: &Client,
+    api_url: &str,
+) -> Result<Vec<Vec<String>>> {
+    let api: Api = Api::new(client, api_url, conf);
+    // the api matches title by it's own `title` parameter.
+    let query_param: &str = if conf.match_in_title() { "title" } else { "q" };
+    let mut sites_links: Vec<Vec<String>> = vec![];
+    for site in conf.sites() {
+        let params: String = format!(
+            "order=desc&sort=relevance&{}={}&site={}",
+            query_param,
+            encode(query),
+            api_site(site)
+        );
+        let questions: Vec<Question> = api.get("/search/advanced", &params).await?;
+        sites_links.push(questions.into_iter().map(|item| item.link).collect());
+    }
+    Ok(sites_links)
+}
+
+/// Get answers of the given questions through the api.
+///
+/// # Arguments
+///
+/// * `question_ids` - ids of questions.
+/// * `site` - The StackExchange site which the questions belong to.
+/// * `conf` - User config, which contains api key and filter.
+/// * `client` - An instance of `request::Client` object which can use to fire http request.
+///
+/// # Returns
+///
+/// Answers of these questions, the highest voted goes first.
+pub async fn question_answers(
+    question_ids: &[u64],
+    site: &str,
+    conf: &Config,
+    client: &Client,
+) -> Result<Vec<ApiAnswer>> {
+    answers_from(question_ids, site, conf, client, API_URL).await
+}
+
+pub(crate) async fn answers_from(
+    question_ids: &[u64],
+    site: &str,
+    conf: &Config,
+    client: &Client,
+    api_url: &str,
+) -> Result<Vec<ApiAnswer>> {
+    let ids: Vec<String> = question_ids.iter().map(u64::to_string).collect();
+    let path: String = format!("/questions/{}/answers", ids.join(";"));
+    // all answers of the questions should be given in one page.
+    let params: String = format!(
+        "order=desc&sort=votes&pagesize={}&site={}",
+        MAX_IDS,
+        api_site(site)
+    );
+    Api::new(client, api_url, conf).get(&path, &params).await
+}
+
+/// Get the api parameter of site, like `stackoverflow` for `stackoverflow.com`
+/// and `unix` for `unix.stackexchange.com`.  The api accepts full domain too,
+/// so other sites are given as they are.
+fn api_site(site: &str) -> &str {
+    if let Some(name) = site.strip_suffix(".stackexchange.com") {
+        return name;
+    }
+    match site.strip_suffix(".com") {
+        Some(name) if !name.contains('.') => name,
+        _ => site,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputOption;
+    use crate::test_utils::{MockResponse, MockServer};
+
+    const SEARCH_RESPONSE: &str = r#"{
+        "items": [
+            {"question_id": 1, "link": "https://stackoverflow.com/questions/1/parse-json"},
+            {"question_id": 2, "link": "https://stackoverflow.com/questions/2/json-in-rust"}
+        ],
+        "has_more": false,
+        "quota_max": 300,
+        "quota_remaining": 299
+    }"#;
+
+    #[test]
+    fn test_get_query_url() {
+        let engine = StackExchange;
+        assert_eq!(
+            engine.get_query_url("parse json", "stackoverflow.com", true),
+            "https://api.stackexchange.com/2.3/search/advanced?order=desc&sort=relevance&q=parse+json&site=stackoverflow"
+        );
+    }
+
+    #[test]
+    fn test_api_site() {
+        assert_eq!(api_site("stackoverflow.com"), "stackoverflow");
+        assert_eq!(api_site("serverfault.com"), "serverfault");
+        assert_eq!(api_site("unix.stackexchange.com"), "unix");
+        assert_eq!(api_site("meta.stackoverflow.com"), "meta.stackoverflow.com");
+        assert_eq!(api_site("stackoverflow"), "stackoverflow");
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let engine = StackExchange;
+        assert_eq!(
+            engine.extract_links(SEARCH_RESPONSE, "stackoverflow.com"),
+            Some(vec![
+                String::from("https://stackoverflow.com/questions/1/parse-json"),
+                String::from("https://stackoverflow.com/questions/2/json-in-rust"),
+            ])
+        );
+        assert!(engine
+            .extract_links(r#"{"items": []}"#, "stackoverflow.com")
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_links() {
+        let server = MockServer::start(vec![MockResponse::new(200, SEARCH_RESPONSE)]);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_se_api_key(Some(String::from("secret")));
+
+        let links: Vec<Vec<String>> =
+            search_links("parse json", &conf, &Client::new(), &server.url("/2.3"))
+                .await
+                .unwrap();
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].len(), 2);
+        let request: &str = &server.requests()[0];
+        assert!(request.starts_with(
+            "GET /2.3/search/advanced?order=desc&sort=relevance&q=parse+json&site=stackoverflow&filter=withbody&key=secret "
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_search_links_respects_backoff() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, r#"{"items": [], "backoff": 1}"#),
+            MockResponse::new(200, SEARCH_RESPONSE),
+        ]);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_sites(vec![
+            String::from("serverfault.com"),
+            String::from("stackoverflow.com"),
+        ]);
+
+        let start: Instant = Instant::now();
+        let links: Vec<Vec<String>> =
+            search_links("parse json", &conf, &Client::new(), &server.url("/2.3"))
+                .await
+                .unwrap();
+
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(links[0].len(), 0);
+        assert_eq!(links[1].len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_links_when_quota_is_exhausted() {
+        let server = MockServer::start(vec![MockResponse::new(
+            400,
+            r#"{"error_id": 502, "error_message": "too many requests from this IP", "error_name": "throttle_violation"}"#,
+        )]);
+        let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+
+        let result: Result<Vec<Vec<String>>> =
+            search_links("parse json", &conf, &Client::new(), &server.url("/2.3")).await;
+
+        match result {
+            Err(Error::QuotaExhausted(message)) => {
+                assert_eq!(message, "too many requests from this IP")
+            }
+            other => panic!("Quota exhausted error is expected, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_question_answers() {
+        let server = MockServer::start(vec![MockResponse::new(
+            200,
+            r#"{"items": [
+                {"answer_id": 10, "question_id": 1, "score": 42, "is_accepted": true, "body": "<p>hors</p>"}
+            ]}"#,
+        )]);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_se_filter(Some(String::from("!custom")));
+
+        let answers: Vec<ApiAnswer> = answers_from(
+            &[1, 2],
+            "stackoverflow.com",
+            &conf,
+            &Client::new(),
+            &server.url("/2.3"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            answers,
+            vec![ApiAnswer {
+                answer_id: 10,
+                question_id: 1,
+                score: 42,
+                is_accepted: true,
+                body: Some(String::from("<p>hors</p>")),
+            }]
+        );
+        assert!(server.requests()[0].starts_with(
+            "GET /2.3/questions/1;2/answers?order=desc&sort=votes&pagesize=100&site=stackoverflow&filter=%21custom "
+        ));
+    }
+}
@@ -13,6 +13,24 @@ pub enum Error {
     IOError(IOError),
     SedesError(SedesError),
     Parse(&'static str),
+    /// Search results seem unrelated to the query, contains the best-guess links.
+    LowConfidence(Vec<String>),
+    /// Request quota of Stack Exchange API is used up, contains the error message.
+    QuotaExhausted(String),
+    /// Stack Exchange API returns an error, contains error id and message.
+    Api(String),
+    /// User config is invalid, like an unknown theme or color depth, or a proxy
+    /// url which can't be used, contains the reason.
+    Config(String),
+    /// Request times out, contains the link which is requested.
+    Timeout(String),
+    /// Search engine rate limits us, e.g: replies 429 or a captcha page,
+    /// contains the link which is requested.
+    RateLimited(String),
+    /// Nothing is found by search engine, contains the query.
+    NoResult(String),
+    /// None of question pages gives an answer, contains the question links.
+    NoAnswer(Vec<String>),
 }
 
 impl StdError for Error {
@@ -20,6 +38,14 @@ impl StdError for Error {
         match self {
             Error::Network(network_err) => network_err.source(),
             Error::Parse(_) => None,
+            Error::LowConfidence(_) => None,
+            Error::QuotaExhausted(_) => None,
+            Error::Api(_) => None,
+            Error::Config(_) => None,
+            Error::Timeout(_) => None,
+            Error::RateLimited(_) => None,
+            Error::NoResult(_) => None,
+            Error::NoAnswer(_) => None,
             Error::IOError(io_err) => io_err.source(),
             Error::SedesError(sedes_err) => sedes_err.source(),
         }
@@ -28,7 +54,26 @@ impl StdError for Error {
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Error::Network(err) => write!(f, "Network error: {}", err),
+            Error::IOError(err) => write!(f, "IO error: {}", err),
+            Error::SedesError(err) => write!(f, "Cache can't be serialized: {}", err),
+            Error::Parse(reason) => write!(f, "{}", reason),
+            Error::LowConfidence(links) => write!(
+                f,
+                "Search results seem unrelated to the query: {}",
+                links.join(", ")
+            ),
+            Error::QuotaExhausted(message) => {
+                write!(f, "Quota of Stack Exchange API is used up: {}", message)
+            }
+            Error::Api(message) => write!(f, "Stack Exchange API error: {}", message),
+            Error::Config(message) => write!(f, "Invalid config: {}", message),
+            Error::Timeout(link) => write!(f, "Request to {} times out", link),
+            Error::RateLimited(link) => write!(f, "Rate limited by search engine: {}", link),
+            Error::NoResult(query) => write!(f, "Can't find search result of {:?}", query),
+            Error::NoAnswer(links) => write!(f, "Can't get answer from {}", links.join(", ")),
+        }
     }
 }
 
@@ -36,6 +81,15 @@ impl Error {
     pub fn from_parse(reason: &'static str) -> Error {
         Error::Parse(reason)
     }
+
+    /// Check if the error is transient, so the caller may retry later, e.g:
+    /// network errors or being rate limited.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Error::Network(_) | Error::Timeout(_) | Error::RateLimited(_)
+        )
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -55,3 +109,36 @@ impl From<SedesError> for Error {
         Error::SedesError(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Error::NoResult(String::from("parse json")).to_string(),
+            "Can't find search result of \"parse json\""
+        );
+        assert_eq!(
+            Error::from_parse("Can't find question id").to_string(),
+            "Can't find question id"
+        );
+        assert_eq!(
+            Error::Config(String::from("Theme dark is not found")).to_string(),
+            "Invalid config: Theme dark is not found"
+        );
+        assert_eq!(
+            Error::Timeout(String::from("https://stackoverflow.com")).to_string(),
+            "Request to https://stackoverflow.com times out"
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(Error::RateLimited(String::from("https://www.google.com")).is_retryable());
+        assert!(Error::Timeout(String::from("https://stackoverflow.com")).is_retryable());
+        assert!(!Error::NoResult(String::from("parse json")).is_retryable());
+        assert!(!Error::Config(String::from("invalid")).is_retryable());
+    }
+}
@@ -9,13 +9,15 @@
 //!
 //! ```rust
 //! use std::str::FromStr;
-//! use hors::{self, SearchEngine};
+//! use hors::{self, Config, OutputOption, SearchEngine};
 //!
 //! # async fn run() {
 //! let search_engine: SearchEngine = SearchEngine::from_str("bing").unwrap();
+//! let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
 //! let target_links: Vec<String> = hors::search_links(
 //!     "how to parse json in rust",
 //!     search_engine,
+//!     &conf,
 //! )
 //! .await
 //! .unwrap();
@@ -61,11 +63,13 @@
 //!
 //! # async fn run() {
 //! let search_engine: SearchEngine = SearchEngine::from_str("bing").unwrap();
+//! let conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
 //! // please make sure that `cookie_store` should set to `true` in client builder.
 //! let mut client: Client = ClientBuilder::new().cookie_store(true).build().unwrap();
 //! let target_links: Vec<String> = hors::search_links_with_client(
 //!     "how to parse json in rust",
 //!     search_engine,
+//!     &conf,
 //!     &client
 //! )
 //! .await
@@ -81,12 +85,25 @@
 extern crate log;
 
 pub mod answer;
+pub mod cache;
 pub mod config;
 pub mod engine;
 mod error;
-mod utils;
+pub mod paths;
+mod retry;
+#[cfg(test)]
+mod test_utils;
+pub mod utils;
 
-pub use answer::{get_answers, get_answers_with_client, SPLITTER};
-pub use config::{Config, OutputOption, SearchEngine};
-pub use engine::{search_links, search_links_with_client};
+pub use answer::{
+    get_answer_summaries, get_answer_summaries_with_client, get_answers, get_answers_from_file,
+    get_answers_streamed, get_answers_with_client, parse_answers_from_html, prefetch,
+    prefetch_with_client, AnswerSummary, SPLITTER,
+};
+pub use cache::{search_answers, search_answers_with_client};
+pub use config::{ColorDepth, Config, ConfigBuilder, OutputOption, Rank, SearchEngine};
+pub use engine::{
+    check_engine, check_engine_with_client, dry_run_with_client, search_links,
+    search_links_with_client,
+};
 pub use error::{Error, Result};
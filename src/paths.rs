@@ -0,0 +1,60 @@
+//! Resolve directories used by hors.
+//!
+//! On Linux `XDG_CONFIG_HOME` and `XDG_CACHE_HOME` are respected, on macOS
+//! and Windows the platform specific directories are used.
+use directories::BaseDirs;
+use std::path::{Path, PathBuf};
+
+const APP_NAME: &str = "hors";
+
+/// Directory to put hors cache files, e.g: `$XDG_CACHE_HOME/hors`.
+///
+/// # Returns
+///
+/// Return None if the home directory of current user can't be found.
+pub fn cache_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|base_dirs| app_dir(base_dirs.cache_dir()))
+}
+
+/// Directory to put hors config files, e.g: `$XDG_CONFIG_HOME/hors`.
+///
+/// # Returns
+///
+/// Return None if the home directory of current user can't be found.
+pub fn config_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|base_dirs| app_dir(base_dirs.config_dir()))
+}
+
+/// Directory of hors under the given base directory, like `$XDG_CACHE_HOME`.
+fn app_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join(APP_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_dir_under_xdg_dirs() {
+        // environment variables are not changed here, because other tests
+        // resolve the directories at the same time.
+        assert_eq!(
+            app_dir(Path::new("/tmp/hors-xdg/cache")),
+            PathBuf::from("/tmp/hors-xdg/cache/hors")
+        );
+        assert_eq!(
+            app_dir(Path::new("/tmp/hors-xdg/config")),
+            PathBuf::from("/tmp/hors-xdg/config/hors")
+        );
+    }
+
+    #[test]
+    fn test_dirs_end_with_app_name() {
+        if let Some(cache) = cache_dir() {
+            assert!(cache.ends_with(APP_NAME));
+        }
+        if let Some(config) = config_dir() {
+            assert!(config.ends_with(APP_NAME));
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Retry http requests on transient failures, with exponential backoff.
+use crate::config::Config;
+use crate::error::{Error, Result};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+use tokio::time::delay_for;
+
+/// Send the request, and retry it when it fails with timeout, 5xx or 429.
+///
+/// It's retried for at most `conf.max_retries()` times, and the delay before
+/// each retry is doubled from `conf.retry_base_delay()`.
+///
+/// # Returns
+///
+/// The response which isn't retryable, e.g: 200 or 404, or the last response
+/// or error once all retries are used up.  `Error::Timeout` is returned if
+/// the last request times out.
+pub async fn send_with_retry(request: RequestBuilder, conf: &Config) -> Result<Response> {
+    let mut delay: Duration = conf.retry_base_delay();
+    let mut retries: u32 = 0;
+    loop {
+        // request with a streaming body can't be cloned, so it's sent only once.
+        let attempt: RequestBuilder = match request.try_clone() {
+            Some(attempt) if retries < conf.max_retries() => attempt,
+            _ => return send(request).await,
+        };
+        match attempt.send().await {
+            Ok(resp) if !is_retryable_status(resp.status()) => return Ok(resp),
+            Ok(resp) => warn!(
+                "Request to {} failed with status {}",
+                resp.url(),
+                resp.status()
+            ),
+            Err(err) if err.is_timeout() => warn!("Request timed out: {}", err),
+            Err(err) => return Err(err.into()),
+        }
+        debug!("Retry request after {:?}", delay);
+        delay_for(delay).await;
+        delay *= 2;
+        retries += 1;
+    }
+}
+
+/// Send the request, timeout error is reported with the requested link.
+async fn send(request: RequestBuilder) -> Result<Response> {
+    request.send().await.map_err(|err| match err.url() {
+        Some(url) if err.is_timeout() => Error::Timeout(url.to_string()),
+        _ => err.into(),
+    })
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputOption;
+    use crate::test_utils::{MockResponse, MockServer};
+    use crate::utils::client_builder;
+    use reqwest::Client;
+
+    fn retry_conf(max_retries: u32) -> Config {
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_max_retries(max_retries);
+        conf.set_retry_base_delay(Duration::from_millis(10));
+        conf
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_when_server_is_unavailable() {
+        let server = MockServer::start(vec![
+            MockResponse::new(503, "unavailable"),
+            MockResponse::new(503, "unavailable"),
+            MockResponse::new(200, "hors"),
+        ]);
+        let request: RequestBuilder = Client::new().get(&server.url("/"));
+
+        let resp: Response = send_with_retry(request, &retry_conf(3)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.text().await.unwrap(), "hors");
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_when_retries_are_used_up() {
+        let server = MockServer::start(vec![MockResponse::new(429, "slow down")]);
+        let request: RequestBuilder = Client::new().get(&server.url("/"));
+
+        let resp: Response = send_with_retry(request, &retry_conf(2)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(server.hits(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_when_not_found() {
+        let server = MockServer::start(vec![MockResponse::new(404, "not found")]);
+        let request: RequestBuilder = Client::new().get(&server.url("/"));
+
+        let resp: Response = send_with_retry(request, &retry_conf(3)).await.unwrap();
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(server.hits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_when_request_times_out() {
+        let server = MockServer::start(vec![
+            MockResponse::new(200, "hors").with_delay(Duration::from_secs(2))
+        ]);
+        let mut conf: Config = retry_conf(1);
+        conf.set_timeout(Some(Duration::from_millis(200)));
+        let client: Client = client_builder(&conf).unwrap().build().unwrap();
+        let link: String = server.url("/questions/1/test-question");
+
+        let result: Result<Response> = send_with_retry(client.get(&link), &conf).await;
+
+        match result {
+            Err(Error::Timeout(timed_out)) => assert_eq!(timed_out, link),
+            other => panic!("Timeout error is expected, got {:?}", other),
+        }
+        assert_eq!(server.hits(), 2);
+    }
+}
@@ -0,0 +1,161 @@
+//! Helpers for testing hors without touching the real network.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A canned response which is replied by `MockServer`.
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    status: u16,
+    body: String,
+    /// Extra headers of the response, like `Set-Cookie: a=1`.
+    headers: Vec<String>,
+    /// How long to wait before replying.
+    delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: &str) -> MockResponse {
+        MockResponse {
+            status,
+            body: body.to_string(),
+            headers: vec![],
+            delay: Duration::from_secs(0),
+        }
+    }
+
+    /// Add a header to the response.
+    pub fn with_header(mut self, name: &str, value: &str) -> MockResponse {
+        self.headers.push(format!("{}: {}\r\n", name, value));
+        self
+    }
+
+    /// Reply the response after the given delay, to mock a slow server.
+    pub fn with_delay(mut self, delay: Duration) -> MockResponse {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A tiny http server which listens to a random local port.
+///
+/// The server replies the given responses in order, and the last response is
+/// replied over and over again once all responses are consumed.
+pub struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub fn start(responses: Vec<MockResponse>) -> MockServer {
+        assert!(
+            !responses.is_empty(),
+            "mock server needs at least one response"
+        );
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        let requests: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&requests);
+
+        thread::spawn(move || {
+            let mut responses = responses.into_iter().peekable();
+            let mut current: Option<MockResponse> = None;
+            for stream in listener.incoming() {
+                let mut stream: TcpStream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let request: String = read_request_head(&mut stream);
+                recorded.lock().unwrap().push(request);
+                if responses.peek().is_some() {
+                    current = responses.next();
+                }
+                let response: MockResponse = current.clone().unwrap();
+                thread::spawn(move || reply(stream, response));
+            }
+        });
+        MockServer { addr, requests }
+    }
+
+    /// Get url to the given path of this server.
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// How many requests this server received.
+    pub fn hits(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Head of requests this server received, in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request_head(stream: &mut TcpStream) -> String {
+    let mut head: Vec<u8> = vec![];
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => head.extend_from_slice(&buf[..n]),
+        }
+    }
+    String::from_utf8_lossy(&head).into_owned()
+}
+
+fn reply(mut stream: TcpStream, response: MockResponse) {
+    thread::sleep(response.delay);
+    let raw: String = format!(
+        "HTTP/1.1 {} MOCK\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.headers.concat(),
+        response.body.len(),
+        response.body
+    );
+    let _ = stream.write_all(raw.as_bytes());
+}
+
+/// An allocator which records allocated bytes of each thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let _ = ALLOCATED.try_with(|allocated| {
+                allocated.set(allocated.get() + layout.size());
+                let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+            });
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        let _ = ALLOCATED
+            .try_with(|allocated| allocated.set(allocated.get().saturating_sub(layout.size())));
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Run the function, and record the peak of bytes it allocated in current thread.
+pub fn peak_allocated<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let start: usize = ALLOCATED.with(Cell::get);
+    PEAK.with(|peak| peak.set(start));
+    let result: T = f();
+    let peak: usize = PEAK.with(Cell::get);
+    (result, peak - start)
+}
@@ -1,19 +1,37 @@
+use crate::config::{check_proxy, Config};
+use crate::error::Result;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use reqwest::ClientBuilder;
+use url::Url;
 
-static USER_AGENTS: [&str; 6] =
-    [
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.14; rv:66.0) Gecko/20100101 Firefox/66.0",
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:66.0) Gecko/20100101 Firefox/66.0",
-        "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/73.0.3683.103 Safari/537.36",
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/64.0.3282.140 Safari/537.36 Edge/18.17763",
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_14_3) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/12.0.3 Safari/605.1.15",
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/71.0.3578.98 Safari/537.36 OPR/58.0.3135.132",
-    ];
+/// User-Agents of recent browsers across systems, one of them is picked for
+/// each request.
+static USER_AGENTS: [&str; 16] = [
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/84.0.4147.105 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/84.0.4147.105 Safari/537.36",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/84.0.4147.89 Safari/537.36",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/73.0.3683.103 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:79.0) Gecko/20100101 Firefox/79.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:79.0) Gecko/20100101 Firefox/79.0",
+    "Mozilla/5.0 (X11; Ubuntu; Linux x86_64; rv:79.0) Gecko/20100101 Firefox/79.0",
+    "Mozilla/5.0 (X11; Linux x86_64; rv:78.0) Gecko/20100101 Firefox/78.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.14; rv:66.0) Gecko/20100101 Firefox/66.0",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:66.0) Gecko/20100101 Firefox/66.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_6) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/13.1.2 Safari/605.1.15",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_14_3) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/12.0.3 Safari/605.1.15",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/84.0.4147.105 Safari/537.36 Edg/84.0.522.52",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/84.0.4147.105 Safari/537.36 Edg/84.0.522.52",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/64.0.3282.140 Safari/537.36 Edge/18.17763",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/71.0.3578.98 Safari/537.36 OPR/58.0.3135.132",
+];
 static DEFAULT_AGENT: &str = "hors";
 
 /// Generate a random User-Agents.
 ///
+/// It should be called for each request rather than shared between requests,
+/// so the requests don't look the same.
+///
 /// # Returns
 ///
 /// A User-Agent str which can be used for User-Agent fields.
@@ -28,12 +46,188 @@ pub fn random_agent() -> &'static str {
     }
 }
 
+/// Check if the link is a question of any Stack Exchange site, e.g.
+/// `https://superuser.com/questions/1/test` or the short link
+/// `https://unix.stackexchange.com/q/1`.
+///
+/// It checks the path only, so links of tag or user pages are not questions,
+/// but links to sites other than stackoverflow.com are.
+pub fn is_question_link(link: &str) -> bool {
+    link.contains("/questions/") || link.contains("/q/")
+}
+
+/// Get question id from stackoverflow question link.
+///
+/// # Examples
+///
+/// let id: Option<String> = question_id("https://stackoverflow.com/questions/7771011/parse-json");
+/// assert_eq!(id, Some(String::from("7771011")));
+pub fn question_id(link: &str) -> Option<String> {
+    let url: Url = Url::parse(link).ok()?;
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == "questions" || *segment == "q")?;
+    segments
+        .next()
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(String::from)
+}
+
+/// Get canonical url of stackoverflow question link.
+///
+/// The fragment, query string and question slug are stripped, the scheme is
+/// always `https`, and the `www.` or `m.` prefix of host is removed.  So
+/// different links to the same question have the same canonical url.
+///
+/// # Examples
+///
+/// ```rust
+/// use hors::utils::canonical_question_url;
+///
+/// let url: Option<String> = canonical_question_url(
+///     "http://m.stackoverflow.com/questions/7771011/parse-json?noredirect=1#answer-1",
+/// );
+/// assert_eq!(url, Some(String::from("https://stackoverflow.com/questions/7771011")));
+/// ```
+///
+/// # Returns
+///
+/// The canonical url, or None if the link is not a question link.
+pub fn canonical_question_url(url: &str) -> Option<String> {
+    let id: String = question_id(url)?;
+    let parsed: Url = Url::parse(url).ok()?;
+    let host: &str = parsed.host_str()?;
+    let host: &str = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(host);
+    match parsed.port() {
+        Some(port) => Some(format!("https://{}:{}/questions/{}", host, port, id)),
+        None => Some(format!("https://{}/questions/{}", host, id)),
+    }
+}
+
+/// Make a client builder with cookie store, according to user config.
+///
+/// Each request times out after `conf.timeout()`, and all requests go through
+/// the proxy of config if it's set, else reqwest takes proxy from `HTTP_PROXY`
+/// and `HTTPS_PROXY` environment variables.
+///
+/// # Returns
+///
+/// Error if the proxy of config can't be used.
+pub fn client_builder(conf: &Config) -> Result<ClientBuilder> {
+    let mut builder: ClientBuilder = ClientBuilder::new().cookie_store(true);
+    if let Some(timeout) = conf.timeout() {
+        builder = builder.timeout(timeout);
+    }
+    match conf.proxy() {
+        Some(proxy) => Ok(builder.proxy(check_proxy(proxy)?)),
+        None => Ok(builder),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::OutputOption;
+    use crate::test_utils::{MockResponse, MockServer};
 
     #[test]
     fn test_random_agent() {
         assert!(USER_AGENTS.contains(&random_agent()));
     }
+
+    #[test]
+    fn test_random_agent_rotates() {
+        assert!(USER_AGENTS.len() >= 12);
+        for browser in &["Chrome/", "Firefox/", "Version/", "Edg/"] {
+            assert!(USER_AGENTS.iter().any(|agent| agent.contains(browser)));
+        }
+        let agents: std::collections::HashSet<&str> = (0..50).map(|_| random_agent()).collect();
+        assert!(agents.len() > 1);
+    }
+
+    #[test]
+    fn test_is_question_link() {
+        assert!(is_question_link(
+            "https://stackoverflow.com/questions/7771011/parse-json"
+        ));
+        assert!(is_question_link(
+            "https://superuser.com/questions/1/how-to-ssh"
+        ));
+        assert!(is_question_link("https://unix.stackexchange.com/q/42"));
+        assert!(!is_question_link("https://superuser.com/tags/ssh"));
+        assert!(!is_question_link("https://superuser.com/users/1/hors"));
+    }
+
+    #[test]
+    fn test_question_id() {
+        let id: Option<String> =
+            question_id("https://stackoverflow.com/questions/7771011/parse-json-in-python");
+        assert_eq!(id, Some(String::from("7771011")));
+        assert_eq!(
+            question_id("https://superuser.com/questions/42/how-to-ssh"),
+            Some(String::from("42"))
+        );
+        assert_eq!(
+            question_id("https://unix.stackexchange.com/q/42"),
+            Some(String::from("42"))
+        );
+    }
+
+    #[test]
+    fn test_question_id_when_link_is_not_question() {
+        assert_eq!(question_id("https://stackoverflow.com/tags/rust"), None);
+        assert_eq!(
+            question_id("https://stackoverflow.com/questions/tagged/rust"),
+            None
+        );
+        assert_eq!(question_id("not a link"), None);
+    }
+
+    #[test]
+    fn test_canonical_question_url() {
+        let expected: Option<String> =
+            Some(String::from("https://stackoverflow.com/questions/7771011"));
+        for link in &[
+            "https://stackoverflow.com/questions/7771011/parse-json-in-python",
+            "https://stackoverflow.com/questions/7771011/parse-json-in-python#answer-7771071",
+            "https://stackoverflow.com/questions/7771011/parse-json-in-python?noredirect=1&lq=1",
+            "http://stackoverflow.com/questions/7771011",
+            "https://www.stackoverflow.com/questions/7771011/",
+            "https://m.stackoverflow.com/questions/7771011/parse-json-in-python",
+            "https://StackOverflow.com/questions/7771011/parse-json-in-python",
+        ] {
+            assert_eq!(canonical_question_url(link), expected, "{}", link);
+        }
+    }
+
+    #[test]
+    fn test_canonical_question_url_when_link_is_not_question() {
+        assert_eq!(
+            canonical_question_url("https://stackoverflow.com/tags/rust"),
+            None
+        );
+        assert_eq!(canonical_question_url("https://stackoverflow.com/"), None);
+    }
+
+    #[tokio::test]
+    async fn test_client_builder_with_proxy() {
+        let server = MockServer::start(vec![MockResponse::new(200, "proxied")]);
+        let mut conf: Config = Config::new(OutputOption::OnlyCode, 1, false);
+        conf.set_proxy(Some(server.url(""))).unwrap();
+
+        let client = client_builder(&conf).unwrap().build().unwrap();
+        let page: String = client
+            .get("http://stackoverflow.com/questions/1")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert_eq!(page, "proxied");
+        assert!(server.requests()[0].starts_with("GET http://stackoverflow.com/questions/1 "));
+    }
 }
@@ -1,4 +1,4 @@
-use hors::config::SearchEngine;
+use hors::config::{Config, OutputOption, SearchEngine};
 use hors::engine::search_links_with_client;
 use reqwest::{Client, ClientBuilder};
 use std::str::FromStr;
@@ -13,6 +13,7 @@ async fn test_search_links_with_bing_search_engine() {
     let target_links: Vec<String> = search_links_with_client(
         &String::from("how to parse json in rust"),
         search_engine,
+        &Config::new(OutputOption::Links, 1, false),
         &client,
     )
     .await
@@ -32,6 +33,7 @@ async fn test_search_links_with_google_search_engine() {
     let target_links: Vec<String> = search_links_with_client(
         &String::from("how to parse json in rust"),
         search_engine,
+        &Config::new(OutputOption::Links, 1, false),
         &client,
     )
     .await
@@ -51,6 +53,7 @@ async fn test_search_links_with_duckduckgo_search_engine() {
     let target_links: Vec<String> = search_links_with_client(
         &String::from("how to parse json in rust"),
         search_engine,
+        &Config::new(OutputOption::Links, 1, false),
         &client,
     )
     .await